@@ -0,0 +1,3 @@
+pub mod resolver;
+pub mod scope;
+pub mod type_checker;
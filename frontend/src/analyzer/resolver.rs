@@ -0,0 +1,553 @@
+use std::collections::HashMap;
+
+use crate::{
+    analyzer::scope::{Scope, Symbol, SymbolId, SymbolKind, SymbolTable},
+    ast::types::{ASTBlockType, FunctionDefinition, NodeId, ProgramBlock},
+    errors::{SpannedError, codes::ErrorCode},
+    types::{Span, Spanned},
+};
+
+/// Binds variable and function usages to their definitions.
+///
+/// Every block (`<body>`, `<then>`, `<else>`, `<b>`) introduces a new scope.
+/// Functions are visible in the whole program regardless of definition order,
+/// but function bodies can see only their parameters and own locals.
+pub struct Resolver {
+    table: SymbolTable,
+    scopes: Vec<Scope>,
+    functions: HashMap<String, SymbolId>,
+
+    /// Count of enclosing function definitions
+    fn_depth: usize,
+    errors: Vec<SpannedError>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            table: SymbolTable::default(),
+            scopes: Vec::new(),
+            functions: HashMap::new(),
+            fn_depth: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Resolve names of the whole program
+    pub fn resolve_program(
+        mut self,
+        program: &ASTBlockType,
+    ) -> Result<SymbolTable, Vec<SpannedError>> {
+        let ASTBlockType::Program(program) = program else {
+            return Err(vec![SpannedError::new(
+                "[INTERNAL ERROR] Resolver expects <program> block",
+                Span::default(),
+            )]);
+        };
+
+        let root = Self::root_statements(program);
+        self.declare_functions(&root);
+
+        self.scopes.push(Scope::with_pending(Self::collect_pending(
+            root.iter().copied(),
+        )));
+        for statement in root {
+            self.resolve(statement);
+        }
+        self.scopes.pop();
+
+        if self.errors.is_empty() {
+            Ok(self.table)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    /// Resolve top-level statements of the REPL entry.
+    ///
+    /// Names of previous entries stay visible and can be shadowed by the entry,
+    /// the same way the interpreter overwrites redefined variables and functions.
+    /// Entry with errors is not executed, so its definitions are discarded
+    pub fn resolve_entry(&mut self, statements: &[ASTBlockType]) -> Result<(), Vec<SpannedError>> {
+        let functions = self.functions.clone();
+        for statement in statements {
+            if let ASTBlockType::FunctionDefinition(function) = statement {
+                self.functions.remove(&function.name.value);
+            }
+        }
+        self.declare_functions(&statements.iter().collect::<Vec<_>>());
+
+        self.scopes.push(Scope::with_pending(Self::collect_pending(
+            statements.iter(),
+        )));
+        for statement in statements {
+            self.resolve(statement);
+        }
+
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        self.scopes.pop();
+        self.functions = functions;
+        Err(std::mem::take(&mut self.errors))
+    }
+
+    /// Statements of <head> followed by statements of <main>
+    fn root_statements(program: &ProgramBlock) -> Vec<&ASTBlockType> {
+        let mut root = Vec::new();
+        for block in program.head.iter().chain([&program.main]) {
+            if let ASTBlockType::HeadBlock(statements) | ASTBlockType::MainBlock(statements) = block
+            {
+                root.extend(statements);
+            }
+        }
+        root
+    }
+
+    /// Register all functions before resolving, so they can be called before definition
+    fn declare_functions(&mut self, root: &[&ASTBlockType]) {
+        for statement in root {
+            let ASTBlockType::FunctionDefinition(function) = statement else {
+                continue;
+            };
+
+            if self.functions.contains_key(&function.name.value) {
+                self.errors.push(
+                    SpannedError::new(
+                        format!("Function `{}` is already defined", function.name.value),
+                        function.name.span,
+                    )
+                    .with_code(ErrorCode::Redefinition),
+                );
+                continue;
+            }
+
+            let id = self.table.add(Symbol {
+                name: function.name.value.clone(),
+                kind: SymbolKind::Function,
+                span: function.name.span,
+                const_span: None,
+                symbol_type: None,
+            });
+            self.table.bind(function.id, id);
+            self.functions.insert(function.name.value.clone(), id);
+        }
+    }
+
+    /// Collect names of variables, that defined directly in the block
+    fn collect_pending<'a>(
+        statements: impl Iterator<Item = &'a ASTBlockType>,
+    ) -> HashMap<String, Span> {
+        let mut pending = HashMap::new();
+        for statement in statements {
+            if let ASTBlockType::VariableDefinition(def) = statement {
+                pending
+                    .entry(def.name.value.clone())
+                    .or_insert(def.name.span);
+            }
+        }
+        pending
+    }
+
+    fn resolve(&mut self, node: &ASTBlockType) {
+        match node {
+            ASTBlockType::Program(_) | ASTBlockType::HeadBlock(_) | ASTBlockType::MainBlock(_) => {
+                self.errors.push(SpannedError::new(
+                    "[INTERNAL ERROR] Unexpected root block",
+                    Span::default(),
+                ));
+            }
+            ASTBlockType::VariableDefinition(def) => {
+                self.resolve(&def.value.value);
+                self.define(&def.name, SymbolKind::Variable, def.id, def.const_span);
+            }
+            ASTBlockType::FunctionDefinition(function) => self.resolve_function(function),
+            ASTBlockType::FunctionCall(call) => {
+                match self.functions.get(&call.callee.value) {
+                    Some(id) => self.table.bind(call.id, *id),
+                    None => self.errors.push(
+                        SpannedError::new(
+                            format!("Cannot find function `{}`", call.callee.value),
+                            call.callee.span,
+                        )
+                        .with_code(ErrorCode::UnresolvedName),
+                    ),
+                }
+
+                for arg in &call.args {
+                    self.resolve(&arg.value.value);
+                }
+            }
+            ASTBlockType::VariableAssignment(assign) => {
+                self.resolve(&assign.value.value);
+                if let Some(id) = self.bind_variable(&assign.name, assign.id, assign.span) {
+                    self.check_const_assign(id, assign.span);
+                }
+            }
+            ASTBlockType::VariableAccess(access) => {
+                self.bind_variable(&access.name, access.id, access.span);
+            }
+            ASTBlockType::ConditionalOp(op) => {
+                for branch in &op.branches {
+                    self.resolve(&branch.condition.value);
+                    self.resolve_block(&branch.body.value, Vec::new());
+                }
+
+                if let Some(else_body) = &op.else_body {
+                    self.resolve_block(&else_body.value, Vec::new());
+                }
+            }
+            ASTBlockType::Return(ret) => {
+                if self.fn_depth == 0 {
+                    self.errors.push(
+                        SpannedError::new("<return> cannot be used outside of function", ret.span)
+                            .with_code(ErrorCode::ReturnOutsideFunction),
+                    );
+                }
+
+                if let Some(value) = &ret.value {
+                    self.resolve(&value.value);
+                }
+            }
+            ASTBlockType::MathOp(op) => op.operands.iter().for_each(|o| self.resolve(o)),
+            ASTBlockType::LogicalOp(op) => op.operands.iter().for_each(|o| self.resolve(o)),
+            ASTBlockType::CompareOp(op) => op.operands.iter().for_each(|o| self.resolve(o)),
+            ASTBlockType::Print(print) => print.operands.iter().for_each(|o| self.resolve(o)),
+            ASTBlockType::ForLoop(for_loop) => {
+                self.resolve(&for_loop.start.value);
+                self.resolve(&for_loop.end.value);
+                if let Some(step) = &for_loop.step {
+                    self.resolve(&step.value);
+                }
+
+                let iterator = (&for_loop.iterator, SymbolKind::Iterator, for_loop.id);
+                self.resolve_block(&for_loop.body.value, vec![iterator]);
+            }
+            ASTBlockType::WhileLoop(while_loop) => {
+                self.resolve(&while_loop.condition.value);
+                self.resolve_block(&while_loop.body.value, Vec::new());
+            }
+            ASTBlockType::GroupBlock(group) => self.resolve_block(&group.statements, Vec::new()),
+            ASTBlockType::Value(_) | ASTBlockType::Type(_) => {}
+        }
+    }
+
+    fn resolve_function(&mut self, function: &FunctionDefinition) {
+        // Function bodies cannot see variables of enclosing scopes
+        let outer_scopes = std::mem::take(&mut self.scopes);
+        self.fn_depth += 1;
+
+        let params = function
+            .params
+            .iter()
+            .map(|p| (&p.name, SymbolKind::Parameter, p.id))
+            .collect();
+        self.resolve_block(&function.body.value, params);
+
+        self.fn_depth -= 1;
+        self.scopes = outer_scopes;
+    }
+
+    /// Resolve statements inside new scope with predefined symbols
+    fn resolve_block(
+        &mut self,
+        statements: &[ASTBlockType],
+        predefined: Vec<(&Spanned<String>, SymbolKind, NodeId)>,
+    ) {
+        self.scopes.push(Scope::with_pending(Self::collect_pending(
+            statements.iter(),
+        )));
+
+        for (name, kind, definition) in predefined {
+            self.define(name, kind, definition, None);
+        }
+
+        for statement in statements {
+            self.resolve(statement);
+        }
+
+        self.scopes.pop();
+    }
+
+    /// Define symbol in the innermost scope
+    fn define(
+        &mut self,
+        name: &Spanned<String>,
+        kind: SymbolKind,
+        definition: NodeId,
+        const_span: Option<Span>,
+    ) {
+        let Some(scope) = self.scopes.last() else {
+            return;
+        };
+
+        if let Some(existing) = scope.lookup(&name.value) {
+            let existing_kind = self.table.get(existing).map(|s| s.kind.clone());
+            self.errors.push(
+                SpannedError::new(
+                    match existing_kind {
+                        Some(SymbolKind::Parameter) => {
+                            format!("`{}` is already defined as function parameter", name.value)
+                        }
+                        _ => format!("`{}` is already defined in this scope", name.value),
+                    },
+                    name.span,
+                )
+                .with_code(ErrorCode::Redefinition),
+            );
+            return;
+        }
+
+        let id = self.table.add(Symbol {
+            name: name.value.clone(),
+            kind,
+            span: name.span,
+            const_span,
+            symbol_type: None,
+        });
+        self.table.bind(definition, id);
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.define(&name.value, id);
+        }
+    }
+
+    /// Report assignment to the constant
+    fn check_const_assign(&mut self, id: SymbolId, assign: Span) {
+        let Some(symbol) = self.table.get(id) else {
+            return;
+        };
+
+        if let Some(const_span) = symbol.const_span {
+            let name = symbol.name.clone();
+            self.errors.push(
+                SpannedError::new(format!("Cannot assign to constant `{name}`"), assign)
+                    .with_code(ErrorCode::AssignToConstant)
+                    .with_primary_label("constant cannot be reassigned")
+                    .with_label(const_span, format!("`{name}` is declared as constant here"))
+                    .with_help(format!("remove `<const />` to make `{name}` mutable")),
+            );
+        }
+    }
+
+    /// Bind variable usage to the nearest definition
+    fn bind_variable(&mut self, name: &str, usage: NodeId, span: Span) -> Option<SymbolId> {
+        if let Some(id) = self.scopes.iter().rev().find_map(|s| s.lookup(name)) {
+            self.table.bind(usage, id);
+            return Some(id);
+        }
+
+        let message = if self
+            .scopes
+            .iter()
+            .any(|s| s.pending_definition(name).is_some())
+        {
+            format!("Variable `{name}` is used before its definition")
+        } else {
+            format!("Cannot find variable `{name}` in this scope")
+        };
+        self.errors
+            .push(SpannedError::new(message, span).with_code(ErrorCode::UnresolvedName));
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzer::{resolver::Resolver, scope::SymbolKind},
+        ast::{gen_main_ast, types::ASTBlockType},
+        check,
+        errors::{Severity, SpannedError, codes::ErrorCode, error_renderer::ErrorRenderer},
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+        types::{SourceFile, Span},
+    };
+
+    fn resolve_errors(main: &str) -> Vec<String> {
+        let code = format!("<program><main>{main}</main></program>");
+        let node = TokenParser::new(Lexer::new(code).parse()).parse().unwrap();
+        match Resolver::new().resolve_program(&gen_main_ast(&node).unwrap()) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.message().to_owned()).collect(),
+        }
+    }
+
+    #[test]
+    fn binds_access_to_definition() {
+        let code = "<program><main>\
+            <let><name>x</name><value><int>1</int></value></let><x /></main></program>";
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        let ast = gen_main_ast(&node).unwrap();
+        let table = Resolver::new().resolve_program(&ast).unwrap();
+
+        let ASTBlockType::Program(program) = ast else {
+            panic!("Expected program");
+        };
+        let ASTBlockType::MainBlock(statements) = program.main else {
+            panic!("Expected main block");
+        };
+        let (ASTBlockType::VariableDefinition(def), ASTBlockType::VariableAccess(access)) =
+            (&statements[0], &statements[1])
+        else {
+            panic!("Expected definition and access");
+        };
+
+        let symbol = table.resolve(access.id).unwrap();
+        assert_eq!(symbol.name, "x");
+        assert_eq!(symbol.kind, SymbolKind::Variable);
+        assert_eq!(symbol.span, Span::new(20, 34));
+        assert_eq!(table.resolve_id(def.id), table.resolve_id(access.id));
+    }
+
+    #[test]
+    fn undefined_variable() {
+        assert_eq!(
+            resolve_errors("<x />"),
+            ["Cannot find variable `x` in this scope"]
+        );
+    }
+
+    #[test]
+    fn use_before_definition() {
+        assert_eq!(
+            resolve_errors(
+                "<x><int>2</int></x><let><name>x</name><value><int>1</int></value></let>"
+            ),
+            ["Variable `x` is used before its definition"]
+        );
+    }
+
+    #[test]
+    fn duplicate_definition() {
+        assert_eq!(
+            resolve_errors(
+                "<let><name>x</name><value><int>1</int></value></let>\
+                <let><name>x</name><value><int>2</int></value></let>"
+            ),
+            ["`x` is already defined in this scope"]
+        );
+    }
+
+    #[test]
+    fn shadowing_in_nested_scope() {
+        assert!(
+            resolve_errors(
+                "<let><name>x</name><value><int>1</int></value></let>\
+                <b><let><name>x</name><value><x /></value></let><x /></b><x />"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn group_block_scope() {
+        assert_eq!(
+            resolve_errors("<b><let><name>y</name><value><int>1</int></value></let></b><y />"),
+            ["Cannot find variable `y` in this scope"]
+        );
+    }
+
+    #[test]
+    fn loop_iterator_scope() {
+        assert_eq!(
+            resolve_errors(
+                "<for><iterator>i</iterator><start><int>0</int></start><end><int>3</int></end>\
+                <body><i /></body></for><i />"
+            ),
+            ["Cannot find variable `i` in this scope"]
+        );
+    }
+
+    #[test]
+    fn functions_are_hoisted() {
+        assert!(
+            resolve_errors(
+                "<call f></call><fn><name>f</name><params>\
+                <arg><name>a</name><type><int /></type></arg></params>\
+                <body><return><a /></return></body></fn>"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn function_cannot_see_outer_variables() {
+        assert_eq!(
+            resolve_errors(
+                "<let><name>x</name><value><int>1</int></value></let>\
+                <fn><name>f</name><body><x /></body></fn>"
+            ),
+            ["Cannot find variable `x` in this scope"]
+        );
+    }
+
+    #[test]
+    fn undefined_function_and_return_outside() {
+        assert_eq!(
+            resolve_errors("<call g></call><return />"),
+            [
+                "Cannot find function `g`",
+                "<return> cannot be used outside of function"
+            ]
+        );
+    }
+
+    #[test]
+    fn assign_to_constant() {
+        assert_eq!(
+            resolve_errors(
+                "<let><name>x</name><value><int>1</int></value><const /></let>\
+                <b><x><int>2</int></x></b>"
+            ),
+            ["Cannot assign to constant `x`"]
+        );
+    }
+
+    #[test]
+    fn shadowed_constant_is_assignable() {
+        assert!(
+            resolve_errors(
+                "<let><name>x</name><value><int>1</int></value><const /></let>\
+                <b><let><name>x</name><value><int>1</int></value></let><x><int>2</int></x></b>"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn severities_and_codes() {
+        let source = SourceFile::from_string(
+            "<program><main>\n\
+            <let><name>x</name><value><int>1</int></value><const /></let>\n\
+            <x><int>2</int></x>\n\
+            </main></program>",
+        );
+
+        let diagnostics = check(&source).unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity(), Severity::Error);
+        assert_eq!(diagnostics[0].code(), Some(ErrorCode::AssignToConstant));
+
+        let rendered = diagnostics[0].display_with_source(&source);
+        assert!(rendered.starts_with("error[UV0012]: Cannot assign to constant `x`"));
+        assert!(rendered.contains("--------- `x` is declared as constant here"));
+        assert!(rendered.contains("= help: remove `<const />` to make `x` mutable"));
+
+        // Resolver reports only errors, so the warning is built here
+        let warning = SpannedError::new("Variable `x` is never read", Span::new(27, 28))
+            .with_severity(Severity::Warning);
+        assert!(!warning.is_error());
+        let rendered = warning.display_with_source(&source);
+        assert!(rendered.starts_with("warning: Variable `x` is never read"));
+        assert!(rendered.contains("--> <string>:2:11"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::types::{NodeId, UVType},
+    types::Span,
+};
+
+/// Index of the symbol inside `SymbolTable`
+pub type SymbolId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    /// Variable defined with <let>
+    Variable,
+
+    /// Function defined with <fn>
+    Function,
+
+    /// Function parameter
+    Parameter,
+
+    /// Loop variable of <for>
+    Iterator,
+}
+
+/// Named entity, that can be referenced from the code
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+
+    /// Span of the definition
+    pub span: Span,
+
+    /// Span of the `<const />` tag for constants
+    pub const_span: Option<Span>,
+
+    /// Type of the value. Filled by the type checker
+    pub symbol_type: Option<UVType>,
+}
+
+/// All symbols of the program and usages bound to them
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+
+    /// Definition or usage node -> symbol mapping
+    bindings: HashMap<NodeId, SymbolId>,
+}
+
+impl SymbolTable {
+    /// Register new symbol and get its id
+    pub fn add(&mut self, symbol: Symbol) -> SymbolId {
+        self.symbols.push(symbol);
+        self.symbols.len() - 1
+    }
+
+    /// Get symbol by its id
+    pub fn get(&self, id: SymbolId) -> Option<&Symbol> {
+        self.symbols.get(id)
+    }
+
+    /// Set type of the symbol
+    pub fn set_type(&mut self, id: SymbolId, symbol_type: UVType) {
+        if let Some(symbol) = self.symbols.get_mut(id) {
+            symbol.symbol_type = Some(symbol_type);
+        }
+    }
+
+    /// Bind definition or usage node (access, assign or call) to the symbol
+    pub fn bind(&mut self, node: NodeId, id: SymbolId) {
+        self.bindings.insert(node, id);
+    }
+
+    /// Get symbol, that node is bound to
+    pub fn resolve(&self, node: NodeId) -> Option<&Symbol> {
+        self.bindings.get(&node).and_then(|id| self.get(*id))
+    }
+
+    /// Get id of the symbol, that node is bound to
+    pub fn resolve_id(&self, node: NodeId) -> Option<SymbolId> {
+        self.bindings.get(&node).copied()
+    }
+}
+
+/// Single lexical scope
+#[derive(Debug, Default)]
+pub struct Scope {
+    /// Names visible in this scope
+    names: HashMap<String, SymbolId>,
+
+    /// Names, that will be defined later in this scope.
+    /// Used to distinguish use-before-definition from undefined names
+    pending: HashMap<String, Span>,
+}
+
+impl Scope {
+    /// Create scope with names, that defined somewhere in it
+    pub fn with_pending(pending: HashMap<String, Span>) -> Self {
+        Self {
+            names: HashMap::new(),
+            pending,
+        }
+    }
+
+    /// Get symbol defined in this scope by name
+    pub fn lookup(&self, name: &str) -> Option<SymbolId> {
+        self.names.get(name).copied()
+    }
+
+    /// Define name in this scope
+    pub fn define(&mut self, name: &str, id: SymbolId) {
+        self.pending.remove(name);
+        self.names.insert(name.to_owned(), id);
+    }
+
+    /// Get span of the later definition of the name
+    pub fn pending_definition(&self, name: &str) -> Option<Span> {
+        self.pending.get(name).copied()
+    }
+}
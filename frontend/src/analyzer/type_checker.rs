@@ -0,0 +1,748 @@
+use std::collections::HashMap;
+
+use crate::{
+    analyzer::scope::{SymbolId, SymbolTable},
+    ast::{
+        traits::{GetType, GetTypeScope, IsAssignable},
+        types::{
+            ASTBlockType, CompareOp, CompareOpType, ForLoop, FunctionCall, FunctionDefinition,
+            LogicalOp, MathOp, MathOpType, UVType,
+        },
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    types::{Positional, Span, Spanned},
+};
+
+/// Parameters and return type of the function
+#[derive(Debug, Clone)]
+struct FunctionSignature {
+    name: String,
+    params: Vec<(String, UVType)>,
+    return_type: UVType,
+}
+
+/// Infers types of expressions and reports mismatches.
+///
+/// Must be invoked after the `Resolver`, because types of variables
+/// are stored in the resolved `SymbolTable`.
+pub struct TypeChecker<'a> {
+    symbols: &'a mut SymbolTable,
+    signatures: HashMap<SymbolId, FunctionSignature>,
+
+    /// Return type of the function, that is being checked
+    return_type: Option<UVType>,
+
+    /// Span of the declared return type of the function, that is being checked
+    return_type_span: Option<Span>,
+    errors: Vec<SpannedError>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(symbols: &'a mut SymbolTable) -> Self {
+        Self {
+            symbols,
+            signatures: HashMap::new(),
+            return_type: None,
+            return_type_span: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Check types of the whole program
+    pub fn check_program(mut self, program: &ASTBlockType) -> Result<(), Vec<SpannedError>> {
+        let ASTBlockType::Program(program) = program else {
+            return Err(vec![SpannedError::new(
+                "[INTERNAL ERROR] Type checker expects <program> block",
+                Span::default(),
+            )]);
+        };
+
+        let mut root = Vec::new();
+        for block in program.head.iter().chain([&program.main]) {
+            if let ASTBlockType::HeadBlock(statements) | ASTBlockType::MainBlock(statements) = block
+            {
+                root.extend(statements);
+            }
+        }
+
+        // Signatures are collected first, so functions can be called before definition
+        for statement in &root {
+            if let ASTBlockType::FunctionDefinition(function) = statement {
+                self.declare_function(function);
+            }
+        }
+
+        for statement in root {
+            self.infer(statement);
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn declare_function(&mut self, function: &FunctionDefinition) {
+        let Some(id) = self.symbols.resolve_id(function.id) else {
+            return;
+        };
+
+        self.signatures.insert(
+            id,
+            FunctionSignature {
+                name: function.name.value.clone(),
+                params: function
+                    .params
+                    .iter()
+                    .map(|p| (p.name.value.clone(), p.param_type.value.clone()))
+                    .collect(),
+                return_type: Self::declared_return_type(function),
+            },
+        );
+    }
+
+    fn declared_return_type(function: &FunctionDefinition) -> UVType {
+        function
+            .return_type
+            .as_ref()
+            .map(|t| t.value.clone())
+            .unwrap_or(UVType::Null)
+    }
+
+    /// Infer type of the node. Returns `None` if type cannot be inferred due to errors
+    fn infer(&mut self, node: &ASTBlockType) -> Option<UVType> {
+        match node {
+            ASTBlockType::Program(_) | ASTBlockType::HeadBlock(_) | ASTBlockType::MainBlock(_) => {
+                self.errors.push(SpannedError::new(
+                    "[INTERNAL ERROR] Unexpected root block",
+                    node.get_span(),
+                ));
+                None
+            }
+            ASTBlockType::VariableDefinition(def) => {
+                let value_type = self.infer(&def.value.value);
+
+                if let (Some(declared), Some(value_type)) = (&def.var_type, &value_type)
+                    && !declared.value.is_assignable_from(value_type)
+                {
+                    self.errors.push(
+                        SpannedError::new(
+                            format!(
+                                "Cannot initialize variable `{}` of type `{}` with value of type `{value_type}`",
+                                def.name.value, declared.value
+                            ),
+                            def.value.span,
+                        )
+                        .with_code(ErrorCode::TypeMismatch)
+                        .with_primary_label(format!("expected `{}`, found `{value_type}`", declared.value))
+                        .with_label(declared.span, "expected due to this type"),
+                    );
+                }
+
+                // Declared type takes precedence, so later assignments are checked against it
+                let var_type = match &def.var_type {
+                    Some(declared) => Some(declared.value.clone()),
+                    None => value_type,
+                };
+
+                if let (Some(id), Some(t)) = (self.symbols.resolve_id(def.id), var_type) {
+                    self.symbols.set_type(id, t);
+                }
+                Some(UVType::Null)
+            }
+            ASTBlockType::FunctionDefinition(function) => {
+                self.check_function(function);
+                Some(UVType::Null)
+            }
+            ASTBlockType::FunctionCall(call) => self.check_call(call),
+            ASTBlockType::VariableAssignment(assign) => {
+                let value_type = self.infer(&assign.value.value)?;
+                let var_type = assign.get_type_from_scope(self.symbols)?;
+
+                if !var_type.is_assignable_from(&value_type) {
+                    let mut error = SpannedError::new(
+                        format!(
+                            "Cannot assign value of type `{value_type}` to variable `{}` of type `{var_type}`",
+                            assign.name
+                        ),
+                        assign.value.span,
+                    )
+                    .with_code(ErrorCode::TypeMismatch)
+                    .with_primary_label(format!("expected `{var_type}`, found `{value_type}`"));
+
+                    if let Some(symbol) = self.symbols.resolve(assign.id) {
+                        error = error.with_label(
+                            symbol.span,
+                            format!("`{}` is defined here with type `{var_type}`", assign.name),
+                        );
+                    }
+                    self.errors.push(error);
+                }
+                Some(UVType::Null)
+            }
+            ASTBlockType::VariableAccess(access) => access.get_type_from_scope(self.symbols),
+            ASTBlockType::ConditionalOp(op) => {
+                for branch in &op.branches {
+                    self.expect_condition(&branch.condition);
+                    self.check_block(&branch.body.value);
+                }
+
+                if let Some(else_body) = &op.else_body {
+                    self.check_block(&else_body.value);
+                }
+                Some(UVType::Null)
+            }
+            ASTBlockType::Return(ret) => {
+                let value_type = match &ret.value {
+                    Some(value) => self.infer(&value.value)?,
+                    None => UVType::Null,
+                };
+
+                // Return outside of function is reported by the resolver
+                let expected = self.return_type.clone()?;
+                if !expected.is_assignable_from(&value_type) {
+                    let span = ret.value.as_ref().map(|v| v.span).unwrap_or(ret.span);
+                    let error = SpannedError::new(
+                        format!("Expected return value of type `{expected}`, found `{value_type}`"),
+                        span,
+                    )
+                    .with_code(ErrorCode::TypeMismatch)
+                    .with_primary_label(format!("expected `{expected}`, found `{value_type}`"));
+
+                    self.errors.push(match self.return_type_span {
+                        Some(return_span) => {
+                            error.with_label(return_span, "expected because of this return type")
+                        }
+                        None => {
+                            error.with_help("declare return type of the function with <returns>")
+                        }
+                    });
+                }
+                Some(UVType::Null)
+            }
+            ASTBlockType::MathOp(op) => self.check_math(op),
+            ASTBlockType::LogicalOp(op) => self.check_logical(op),
+            ASTBlockType::CompareOp(op) => self.check_compare(op),
+            ASTBlockType::ForLoop(for_loop) => {
+                self.check_for_loop(for_loop);
+                Some(UVType::Null)
+            }
+            ASTBlockType::WhileLoop(while_loop) => {
+                self.expect_condition(&while_loop.condition);
+                self.check_block(&while_loop.body.value);
+                Some(UVType::Null)
+            }
+            ASTBlockType::Value(value) => Some(value.value.get_type()),
+            // Self-closing <null /> is parsed as a type, but it is also a valid value
+            ASTBlockType::Type(t) if t.value == UVType::Null => Some(UVType::Null),
+            ASTBlockType::Type(t) => {
+                self.error(
+                    ErrorCode::TypeMismatch,
+                    format!("Type `{}` cannot be used as a value", t.value),
+                    t.span,
+                );
+                None
+            }
+            ASTBlockType::GroupBlock(group) => self.check_block(&group.statements),
+            ASTBlockType::Print(print) => {
+                // Any value can be printed
+                self.infer_operands(&print.operands)?;
+                Some(UVType::Null)
+            }
+        }
+    }
+
+    /// Check statements and get type of the last one
+    fn check_block(&mut self, statements: &[ASTBlockType]) -> Option<UVType> {
+        let mut last = Some(UVType::Null);
+        for statement in statements {
+            last = self.infer(statement);
+        }
+        last
+    }
+
+    fn check_function(&mut self, function: &FunctionDefinition) {
+        for param in &function.params {
+            if let Some(id) = self.symbols.resolve_id(param.id) {
+                self.symbols.set_type(id, param.param_type.value.clone());
+            }
+        }
+
+        let return_type = Self::declared_return_type(function);
+        let return_type_span = function.return_type.as_ref().map(|t| t.span);
+        let outer = self.return_type.replace(return_type.clone());
+        let outer_span = std::mem::replace(&mut self.return_type_span, return_type_span);
+        self.check_block(&function.body.value);
+        self.return_type = outer;
+        self.return_type_span = outer_span;
+
+        if let Some(span) = return_type_span
+            && !return_type.is_assignable_from(&UVType::Null)
+            && !Self::always_returns(&function.body.value)
+        {
+            self.errors.push(
+                SpannedError::new(
+                    format!(
+                        "Function `{}` must return a value of type `{return_type}` on every path",
+                        function.name.value
+                    ),
+                    function.name.span,
+                )
+                .with_code(ErrorCode::MissingReturn)
+                .with_label(span, "expected because of this return type")
+                .with_note("function without <return> returns `null`"),
+            );
+        }
+    }
+
+    /// Check if execution of statements always ends with <return>
+    fn always_returns(statements: &[ASTBlockType]) -> bool {
+        match statements.last() {
+            Some(ASTBlockType::Return(_)) => true,
+            Some(ASTBlockType::GroupBlock(group)) => Self::always_returns(&group.statements),
+            Some(ASTBlockType::ConditionalOp(op)) => {
+                op.else_body
+                    .as_ref()
+                    .is_some_and(|body| Self::always_returns(&body.value))
+                    && op
+                        .branches
+                        .iter()
+                        .all(|branch| Self::always_returns(&branch.body.value))
+            }
+            _ => false,
+        }
+    }
+
+    fn check_call(&mut self, call: &FunctionCall) -> Option<UVType> {
+        // Unknown functions are reported by the resolver
+        let id = self.symbols.resolve_id(call.id)?;
+        let signature = self.signatures.get(&id)?.clone();
+
+        let mut passed: Vec<bool> = vec![false; signature.params.len()];
+        for (position, arg) in call.args.iter().enumerate() {
+            let arg_type = self.infer(&arg.value.value);
+
+            let index = match &arg.name {
+                None if position >= signature.params.len() => {
+                    self.error(
+                        ErrorCode::InvalidArguments,
+                        format!(
+                            "Function `{}` takes {} arguments, but more were supplied",
+                            signature.name,
+                            signature.params.len()
+                        ),
+                        arg.span,
+                    );
+                    continue;
+                }
+                None => position,
+                Some(name) => match signature.params.iter().position(|(n, _)| *n == name.value) {
+                    Some(index) => index,
+                    None => {
+                        self.error(
+                            ErrorCode::InvalidArguments,
+                            format!(
+                                "Function `{}` has no parameter `{}`",
+                                signature.name, name.value
+                            ),
+                            name.span,
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            let (param_name, param_type) = &signature.params[index];
+            if passed[index] {
+                self.error(
+                    ErrorCode::InvalidArguments,
+                    format!("Parameter `{param_name}` is passed more than once"),
+                    arg.span,
+                );
+                continue;
+            }
+            passed[index] = true;
+
+            if let Some(arg_type) = arg_type
+                && !param_type.is_assignable_from(&arg_type)
+            {
+                self.error(
+                    ErrorCode::TypeMismatch,
+                    format!(
+                        "Expected `{param_type}` for parameter `{param_name}`, found `{arg_type}`"
+                    ),
+                    arg.value.span,
+                );
+            }
+        }
+
+        for (index, (param_name, _)) in signature.params.iter().enumerate() {
+            if !passed[index] {
+                self.error(
+                    ErrorCode::InvalidArguments,
+                    format!(
+                        "Missing argument for parameter `{param_name}` of function `{}`",
+                        signature.name
+                    ),
+                    call.span,
+                );
+            }
+        }
+
+        Some(signature.return_type)
+    }
+
+    fn check_math(&mut self, op: &MathOp) -> Option<UVType> {
+        let operands = self.infer_operands(&op.operands)?;
+
+        // Sum of strings is a concatenation
+        if matches!(op.op_type, MathOpType::Sum)
+            && matches!(operands.first(), Some((UVType::String, _)))
+        {
+            let mut valid = true;
+            for (t, span) in &operands {
+                if *t != UVType::String {
+                    self.error(
+                        ErrorCode::TypeMismatch,
+                        format!("Cannot concatenate `str` with `{t}`"),
+                        *span,
+                    );
+                    valid = false;
+                }
+            }
+            return valid.then_some(UVType::String);
+        }
+
+        let mut valid = true;
+        for (t, span) in &operands {
+            if !Self::is_numeric(t) {
+                self.error(
+                    ErrorCode::TypeMismatch,
+                    format!("`<{}>` expects numeric operands, found `{t}`", op.op_type),
+                    *span,
+                );
+                valid = false;
+            }
+        }
+
+        valid.then(|| Self::numeric_result(operands.iter().map(|(t, _)| t)))
+    }
+
+    fn check_logical(&mut self, op: &LogicalOp) -> Option<UVType> {
+        let operands = self.infer_operands(&op.operands)?;
+
+        let mut valid = true;
+        for (t, span) in &operands {
+            if *t != UVType::Boolean {
+                self.error(
+                    ErrorCode::TypeMismatch,
+                    format!("`<{}>` expects `bool` operands, found `{t}`", op.op_type),
+                    *span,
+                );
+                valid = false;
+            }
+        }
+
+        valid.then_some(UVType::Boolean)
+    }
+
+    fn check_compare(&mut self, op: &CompareOp) -> Option<UVType> {
+        let operands = self.infer_operands(&op.operands)?;
+        let Some((first, _)) = operands.first() else {
+            return Some(UVType::Boolean);
+        };
+
+        let mut valid = true;
+        match op.op_type {
+            CompareOpType::Equality | CompareOpType::NotEquality => {
+                for (t, span) in &operands[1..] {
+                    if !Self::is_comparable(first, t) {
+                        self.error(
+                            ErrorCode::TypeMismatch,
+                            format!("Cannot compare `{first}` with `{t}`"),
+                            *span,
+                        );
+                        valid = false;
+                    }
+                }
+            }
+            _ => {
+                let ordered = |t: &UVType| Self::is_numeric(t) || *t == UVType::String;
+                for (t, span) in &operands {
+                    if !ordered(t) {
+                        self.error(
+                            ErrorCode::TypeMismatch,
+                            format!(
+                                "`<{}>` expects numeric or `str` operands, found `{t}`",
+                                op.op_type
+                            ),
+                            *span,
+                        );
+                        valid = false;
+                    } else if !Self::is_comparable(first, t) {
+                        self.error(
+                            ErrorCode::TypeMismatch,
+                            format!("Cannot compare `{first}` with `{t}`"),
+                            *span,
+                        );
+                        valid = false;
+                    }
+                }
+            }
+        }
+
+        valid.then_some(UVType::Boolean)
+    }
+
+    fn check_for_loop(&mut self, for_loop: &ForLoop) {
+        let mut bounds = vec![&for_loop.start, &for_loop.end];
+        bounds.extend(&for_loop.step);
+
+        let mut types = Vec::new();
+        for bound in bounds {
+            match self.infer(&bound.value) {
+                Some(t) if Self::is_numeric(&t) => types.push(t),
+                Some(t) => self.error(
+                    ErrorCode::InvalidLoopBounds,
+                    format!("For loop bounds must be numeric, found `{t}`"),
+                    bound.span,
+                ),
+                None => {}
+            }
+        }
+
+        if let Some(id) = self.symbols.resolve_id(for_loop.id) {
+            self.symbols
+                .set_type(id, Self::numeric_result(types.iter()));
+        }
+
+        self.check_block(&for_loop.body.value);
+    }
+
+    /// Check that condition is a boolean expression
+    fn expect_condition(&mut self, condition: &Spanned<Box<ASTBlockType>>) {
+        if let Some(t) = self.infer(&condition.value)
+            && t != UVType::Boolean
+        {
+            self.error(
+                ErrorCode::TypeMismatch,
+                format!("Condition must be of type `bool`, found `{t}`"),
+                condition.value.get_span(),
+            );
+        }
+    }
+
+    /// Infer types of all operands. Returns `None` if any of them cannot be inferred
+    fn infer_operands(&mut self, operands: &[ASTBlockType]) -> Option<Vec<(UVType, Span)>> {
+        let inferred: Vec<Option<(UVType, Span)>> = operands
+            .iter()
+            .map(|o| self.infer(o).map(|t| (t, o.get_span())))
+            .collect();
+
+        inferred.into_iter().collect()
+    }
+
+    fn is_numeric(t: &UVType) -> bool {
+        UVType::new_union(vec![UVType::Int, UVType::Float]).is_assignable_from(t)
+    }
+
+    /// Result of arithmetic: `int` if all operands are integers, `float` otherwise
+    fn numeric_result<'t>(mut types: impl Iterator<Item = &'t UVType>) -> UVType {
+        if types.all(|t| *t == UVType::Int) {
+            UVType::Int
+        } else {
+            UVType::Float
+        }
+    }
+
+    /// Check if values of these types can be equal
+    fn is_comparable(a: &UVType, b: &UVType) -> bool {
+        if Self::is_numeric(a) && Self::is_numeric(b) {
+            return true;
+        }
+
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        a.flatten_into(&mut left);
+        b.flatten_into(&mut right);
+        left.iter().any(|t| right.contains(t))
+    }
+
+    fn error(&mut self, code: ErrorCode, message: impl Into<String>, span: Span) {
+        self.errors
+            .push(SpannedError::new(message, span).with_code(code));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzer::{resolver::Resolver, type_checker::TypeChecker},
+        ast::gen_main_ast,
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+    };
+
+    fn type_errors(main: &str) -> Vec<String> {
+        let code = format!("<program><main>{main}</main></program>");
+        let node = TokenParser::new(Lexer::new(code).parse()).parse().unwrap();
+        let ast = gen_main_ast(&node).unwrap();
+        let mut symbols = Resolver::new().resolve_program(&ast).unwrap();
+
+        match TypeChecker::new(&mut symbols).check_program(&ast) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.message().to_owned()).collect(),
+        }
+    }
+
+    #[test]
+    fn valid_expressions() {
+        assert!(
+            type_errors(
+                "<let><name>x</name><value><sum><int>1</int><float>2.5</float></sum></value></let>\
+                <x><mul><x /><int>2</int></mul></x>\
+                <let><name>s</name><value><sum><str>a</str><str>b</str></sum></value></let>\
+                <and><gt><x /><int>1</int></gt><eq><s /><str>ab</str></eq></and>"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn print_any_values() {
+        assert!(
+            type_errors("<println><int>1</int><str>a</str><bool>1</bool></println>").is_empty()
+        );
+        assert_eq!(
+            type_errors("<println><sum><str>a</str><bool>1</bool></sum></println>"),
+            ["Cannot concatenate `str` with `bool`"]
+        );
+    }
+
+    #[test]
+    fn sum_of_str_and_bool() {
+        assert_eq!(
+            type_errors("<sum><str>a</str><bool>1</bool></sum>"),
+            ["Cannot concatenate `str` with `bool`"]
+        );
+    }
+
+    #[test]
+    fn math_on_non_numeric() {
+        assert_eq!(
+            type_errors("<mul><int>1</int><bool>1</bool></mul>"),
+            ["`<mul>` expects numeric operands, found `bool`"]
+        );
+    }
+
+    #[test]
+    fn logical_over_ints() {
+        assert_eq!(
+            type_errors("<and><int>1</int><int>0</int></and>"),
+            [
+                "`<and>` expects `bool` operands, found `int`",
+                "`<and>` expects `bool` operands, found `int`"
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_mismatch() {
+        assert_eq!(
+            type_errors("<eq><str>1</str><int>1</int></eq>"),
+            ["Cannot compare `str` with `int`"]
+        );
+    }
+
+    #[test]
+    fn assignment_mismatch() {
+        assert_eq!(
+            type_errors("<let><name>x</name><value><int>1</int></value></let><x><str>a</str></x>"),
+            ["Cannot assign value of type `str` to variable `x` of type `int`"]
+        );
+    }
+
+    #[test]
+    fn condition_must_be_bool() {
+        assert_eq!(
+            type_errors("<if><cond><int>1</int></cond><then></then></if>"),
+            ["Condition must be of type `bool`, found `int`"]
+        );
+    }
+
+    #[test]
+    fn union_operand() {
+        assert_eq!(
+            type_errors(
+                "<fn><name>f</name><params><arg><name>a</name><type><union><int /><null /></union>\
+                </type></arg></params><body><sum><a /><int>1</int></sum></body></fn>"
+            ),
+            ["`<sum>` expects numeric operands, found `int | null`"]
+        );
+    }
+
+    #[test]
+    fn function_calls() {
+        let function = "<fn><name>f</name><params>\
+            <arg><name>a</name><type><int /></type></arg>\
+            <arg><name>c</name><type><str /></type></arg></params>\
+            <returns><int /></returns><body><return><a /></return></body></fn>";
+
+        assert!(
+            type_errors(&format!(
+                "{function}<sum><call f><arg><int>1</int></arg><arg c><str>x</str></arg></call>\
+                <int>1</int></sum>"
+            ))
+            .is_empty()
+        );
+
+        assert_eq!(
+            type_errors(&format!(
+                "{function}<call f><arg><str>1</str></arg><arg d><int>1</int></arg></call>"
+            )),
+            [
+                "Expected `int` for parameter `a`, found `str`",
+                "Function `f` has no parameter `d`",
+                "Missing argument for parameter `c` of function `f`"
+            ]
+        );
+    }
+
+    #[test]
+    fn return_type() {
+        assert_eq!(
+            type_errors(
+                "<fn><name>f</name><returns><int /></returns>\
+                <body><return><str>a</str></return></body></fn>\
+                <fn><name>g</name><returns><int /></returns><body></body></fn>"
+            ),
+            [
+                "Expected return value of type `int`, found `str`",
+                "Function `g` must return a value of type `int` on every path"
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_type() {
+        assert!(
+            type_errors(
+                "<let><name>x</name><type><union><int /><null /></union></type>\
+                <value><null /></value></let><x><int>1</int></x><x><null /></x>"
+            )
+            .is_empty()
+        );
+
+        assert_eq!(
+            type_errors(
+                "<let><name>x</name><type><int /></type><value><str>a</str></value></let>\
+                <let><name>y</name><type><union><int /><null /></union></type>\
+                <value><int>1</int></value></let><y><float>1.5</float></y>"
+            ),
+            [
+                "Cannot initialize variable `x` of type `int` with value of type `str`",
+                "Cannot assign value of type `float` to variable `y` of type `int | null`"
+            ]
+        );
+    }
+}
@@ -1,21 +1,23 @@
 use crate::{
     ast::{
-        GeneratorOutputType, generate_ast,
+        AstGenerator, GeneratorOutputType,
+        operands::parse_operands,
         traits::{IsVariadic, StringToUVCompareOp},
         types::{ASTBlockType, CompareOp},
     },
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     tokens_parser::types::UVParseNode,
 };
 
 /// Parse Ultraviolet compare operators
-pub fn parse_compare_op(node: &UVParseNode) -> GeneratorOutputType {
-    let op_type = node
-        .name
-        .to_uvcompare()
-        .ok_or(SpannedError::new("Unknown comparison operation", node.span))?;
+pub fn parse_compare_op(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let op_type = node.name.to_uvcompare().ok_or(
+        SpannedError::new("Unknown comparison operation", node.span)
+            .with_code(ErrorCode::UnexpectedTag),
+    )?;
 
-    let children = parse_arguments(node, !op_type.is_variadic())?;
+    let max = (!op_type.is_variadic()).then_some(2);
+    let children = parse_operands(generator, node, "comparison operation", 2, max)?;
 
     Ok(ASTBlockType::CompareOp(CompareOp {
         op_type,
@@ -23,32 +25,3 @@ pub fn parse_compare_op(node: &UVParseNode) -> GeneratorOutputType {
         span: node.span,
     }))
 }
-
-/// Parse arguments for compare
-fn parse_arguments(node: &UVParseNode, only_two: bool) -> Result<Vec<ASTBlockType>, SpannedError> {
-    if !node.all_tags() {
-        return Err(SpannedError::new(
-            "Unexpected literals inside comparison operation",
-            node.span,
-        ));
-    }
-
-    if node.children_len() < 2 {
-        return Err(SpannedError::new(
-            "Comparison operator cannot have less than 2 operands",
-            node.span,
-        ));
-    }
-
-    if only_two && node.children_len() != 2 {
-        return Err(SpannedError::new(
-            format!("`{}` math operation can handle only 2 arguments", node.name),
-            node.span,
-        ));
-    }
-
-    node.get_all_tags()
-        .into_iter()
-        .map(|ch| generate_ast(ch))
-        .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
-}
@@ -0,0 +1,154 @@
+use crate::{
+    ast::{
+        AstGenerator, GeneratorOutputType, check_extra_children, get_single_child,
+        operands::parse_condition,
+        parse_body,
+        types::{ASTBlockType, ConditionalBranch, ConditionalOp},
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    tokens_parser::types::UVParseNode,
+};
+
+/// Parse conditional operator <if>
+pub fn parse_conditional_op(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> GeneratorOutputType {
+    check_extra_children(
+        node,
+        vec!["cond", "then", "elif", "else"],
+        "Found extra children inside `if` declaration",
+    )?;
+
+    let mut branches = vec![parse_branch(generator, node, "if")?];
+    let mut else_body = None;
+
+    for child in node.get_all_tags() {
+        match child.name.as_str() {
+            "elif" if else_body.is_some() => {
+                return Err(SpannedError::new(
+                    "<elif> branch cannot follow <else> branch",
+                    child.span,
+                )
+                .with_code(ErrorCode::InvalidTagContent));
+            }
+            "elif" => branches.push(parse_branch(generator, child, "elif")?),
+            "else" if else_body.is_some() => {
+                return Err(SpannedError::new(
+                    "Conditional operator can have only one <else> branch",
+                    child.span,
+                )
+                .with_code(ErrorCode::InvalidTagContent));
+            }
+            "else" => else_body = Some(parse_body(generator, child)?),
+            _ => {}
+        }
+    }
+
+    Ok(ASTBlockType::ConditionalOp(ConditionalOp {
+        branches,
+        else_body,
+        span: node.span,
+    }))
+}
+
+/// Parse <cond> and <then> pair of `if` or `elif` branch
+fn parse_branch(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+    kind: &str,
+) -> Result<ConditionalBranch, SpannedError> {
+    if kind == "elif" {
+        check_extra_children(
+            node,
+            vec!["cond", "then"],
+            "Found extra children inside `elif` branch",
+        )?;
+    }
+
+    let owner = format!("`{kind}` branch");
+    let cond_block = get_single_child(node, "cond", &owner)?;
+    let then_block = get_single_child(node, "then", &owner)?;
+
+    Ok(ConditionalBranch {
+        condition: parse_condition(generator, cond_block)?,
+        body: parse_body(generator, then_block)?,
+        span: node.span,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{AstGenerator, GeneratorOutputType, generate_ast, types::ASTBlockType},
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+    };
+
+    fn get_ast(code: &str) -> GeneratorOutputType {
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        generate_ast(&mut AstGenerator::default(), &node)
+    }
+
+    #[test]
+    fn if_then() {
+        let ASTBlockType::ConditionalOp(op) =
+            get_ast("<if><cond><bool>1</bool></cond><then><int>1</int></then></if>").unwrap()
+        else {
+            panic!("Expected conditional operator");
+        };
+
+        assert_eq!(op.branches.len(), 1);
+        assert!(op.else_body.is_none());
+    }
+
+    #[test]
+    fn if_elif_else() {
+        let ASTBlockType::ConditionalOp(op) = get_ast(
+            "<if><cond><bool>0</bool></cond><then></then>\
+            <elif><cond><bool>0</bool></cond><then></then></elif>\
+            <elif><cond><bool>1</bool></cond><then><int>2</int></then></elif>\
+            <else><int>3</int></else></if>",
+        )
+        .unwrap() else {
+            panic!("Expected conditional operator");
+        };
+
+        assert_eq!(op.branches.len(), 3);
+        assert_eq!(op.branches[2].body.value.len(), 1);
+        assert!(op.else_body.is_some());
+    }
+
+    #[test]
+    fn missing_then() {
+        assert!(get_ast("<if><cond><bool>1</bool></cond></if>").is_err());
+    }
+
+    #[test]
+    fn multiple_conditions() {
+        assert!(
+            get_ast("<if><cond><bool>1</bool><bool>1</bool></cond><then></then></if>").is_err()
+        );
+    }
+
+    #[test]
+    fn elif_after_else() {
+        assert!(
+            get_ast(
+                "<if><cond><bool>1</bool></cond><then></then><else></else>\
+                <elif><cond><bool>1</bool></cond><then></then></elif></if>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn duplicated_else() {
+        assert!(
+            get_ast("<if><cond><bool>1</bool></cond><then></then><else></else><else></else></if>")
+                .is_err()
+        );
+    }
+}
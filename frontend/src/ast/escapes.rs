@@ -0,0 +1,285 @@
+use crate::{
+    Diagnostics,
+    errors::{SpannedError, codes::ErrorCode},
+    types::{Positional, Span, Spanned},
+};
+
+/// Entities, that can be written by name
+const NAMED_ENTITIES: [(&str, char); 5] = [
+    ("lt", '<'),
+    ("gt", '>'),
+    ("amp", '&'),
+    ("quot", '"'),
+    ("apos", '\''),
+];
+
+/// Unicode escape can have at most this count of hex digits
+const MAX_UNICODE_DIGITS: usize = 6;
+
+/**
+Decode escape sequences and XML entities of the `<estr>` literal
+
+Content of the literal is never trimmed, so every char is mapped to the span one to one
+and errors point to the exact chars of the sequence. Decoding continues after invalid
+sequence, so all of them are reported
+*/
+pub fn unescape(literal: &Spanned<String>) -> Result<String, Diagnostics> {
+    let chars: Vec<char> = literal.value.chars().collect();
+    let mut output = String::with_capacity(literal.value.len());
+    let mut errors = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let start = literal.span.start + i;
+        let decoded = match chars[i] {
+            '\\' => decode_escape(&chars[i..], start),
+            '&' => decode_entity(&chars[i..], start),
+            ch => Ok((ch, 1)),
+        };
+
+        match decoded {
+            Ok((ch, len)) => {
+                output.push(ch);
+                i += len;
+            }
+            Err(err) => {
+                // Skip the invalid sequence, its span ends after the last checked char
+                i += (err.get_span().end - start).max(1);
+                errors.push(err);
+            }
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(output),
+        false => Err(errors),
+    }
+}
+
+fn escape_error(message: impl Into<String>, start: usize, len: usize) -> SpannedError {
+    SpannedError::new(message, Span::new(start, start + len)).with_code(ErrorCode::InvalidEscape)
+}
+
+/// Decode sequence, that starts with `\`. Returns decoded char and length of the sequence
+fn decode_escape(chars: &[char], start: usize) -> Result<(char, usize), SpannedError> {
+    let ch = match chars.get(1) {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('u') => return decode_unicode_escape(chars, start),
+        Some(ch) => {
+            return Err(
+                escape_error(format!("Unknown character escape `\\{ch}`"), start, 2)
+                    .with_primary_label("unknown escape")
+                    .with_help("use `\\\\` to write a backslash"),
+            );
+        }
+        None => {
+            return Err(escape_error("Unterminated escape sequence", start, 1)
+                .with_help("use `\\\\` to write a backslash"));
+        }
+    };
+    Ok((ch, 2))
+}
+
+/// Decode `\u{...}` sequence
+fn decode_unicode_escape(chars: &[char], start: usize) -> Result<(char, usize), SpannedError> {
+    if chars.get(2) != Some(&'{') {
+        return Err(escape_error("Incorrect unicode escape sequence", start, 2)
+            .with_help("format of unicode escape sequences is `\\u{...}`"));
+    }
+
+    let digits: String = chars[3..]
+        .iter()
+        .take_while(|ch| ch.is_ascii_hexdigit())
+        .collect();
+    let end = 3 + digits.len();
+
+    match chars.get(end) {
+        Some('}') => {}
+        Some(ch) => {
+            return Err(escape_error(
+                format!("Invalid character `{ch}` in unicode escape"),
+                start + end,
+                1,
+            )
+            .with_primary_label("not a hex digit"));
+        }
+        None => {
+            return Err(escape_error("Unterminated unicode escape", start, end)
+                .with_help("terminate the unicode escape with `}`"));
+        }
+    }
+
+    let len = end + 1;
+    if digits.is_empty() {
+        return Err(escape_error("Empty unicode escape", start, len)
+            .with_primary_label("this escape must have at least 1 hex digit"));
+    }
+    if digits.len() > MAX_UNICODE_DIGITS {
+        return Err(escape_error("Overlong unicode escape", start, len)
+            .with_primary_label("must have at most 6 hex digits"));
+    }
+
+    // Count of digits is checked above, so the value always fits into u32
+    let value = u32::from_str_radix(&digits, 16).unwrap();
+    match char::from_u32(value) {
+        Some(ch) => Ok((ch, len)),
+        None => {
+            let label = match value {
+                0xD800..=0xDFFF => "unicode escape must not be a surrogate",
+                _ => "unicode escape must be at most 10FFFF",
+            };
+            Err(escape_error("Invalid unicode character escape", start, len)
+                .with_primary_label(label))
+        }
+    }
+}
+
+/// Decode `&name;`, `&#123;` or `&#x7B;` entity
+fn decode_entity(chars: &[char], start: usize) -> Result<(char, usize), SpannedError> {
+    let name: String = chars[1..]
+        .iter()
+        .take_while(|ch| ch.is_ascii_alphanumeric() || **ch == '#')
+        .collect();
+    let name_len = name.chars().count();
+
+    if chars.get(1 + name_len) != Some(&';') {
+        return Err(
+            escape_error("Unterminated character reference", start, 1 + name_len)
+                .with_help("use `&amp;` to write `&`"),
+        );
+    }
+
+    let len = name_len + 2;
+    if let Some(code) = name.strip_prefix('#') {
+        let value = match code.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => code.parse::<u32>(),
+        };
+
+        return match value.ok().and_then(char::from_u32) {
+            Some(ch) => Ok((ch, len)),
+            None => Err(escape_error(
+                format!("Invalid numeric character reference `&{name};`"),
+                start,
+                len,
+            )
+            .with_primary_label("not a valid unicode code point")),
+        };
+    }
+
+    match NAMED_ENTITIES.iter().find(|(entity, _)| *entity == name) {
+        Some((_, ch)) => Ok((*ch, len)),
+        None => Err(
+            escape_error(format!("Unknown entity `&{name};`"), start, len)
+                .with_primary_label("unknown entity")
+                .with_help("supported entities are `&lt;`, `&gt;`, `&amp;`, `&quot;` and `&apos;`"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::escapes::unescape,
+        errors::{SpannedError, codes::ErrorCode},
+        parse_ast,
+        types::{Positional, SourceFile, Span, Spanned},
+    };
+
+    /// Decode content and get the first error
+    fn decode(content: &str) -> Result<String, SpannedError> {
+        decode_all(content).map_err(|errors| errors.into_iter().next().unwrap())
+    }
+
+    fn decode_all(content: &str) -> Result<String, Vec<SpannedError>> {
+        // Content is placed after `<estr>`
+        unescape(&Spanned::new(
+            content.to_owned(),
+            Span::new(6, 6 + content.chars().count()),
+        ))
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(decode(r"a\nb\t\\\r\0").unwrap(), "a\nb\t\\\r\0");
+        assert_eq!(decode(r"\u{48}\u{1F600}").unwrap(), "H😀");
+        assert_eq!(
+            decode("&lt;/estr&gt; &amp; &quot;&apos;").unwrap(),
+            "</estr> & \"'"
+        );
+        assert_eq!(decode("&#65;&#x42;&#X1f600;").unwrap(), "AB😀");
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let err = decode(r"ab\q").unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::InvalidEscape));
+        assert_eq!(err.message(), r"Unknown character escape `\q`");
+        assert_eq!(err.get_span(), Span::new(8, 10));
+
+        assert_eq!(decode(r"a\").unwrap_err().get_span(), Span::new(7, 8));
+        assert_eq!(decode(r"\u41").unwrap_err().get_span(), Span::new(6, 8));
+        assert_eq!(decode(r"\u{4g}").unwrap_err().get_span(), Span::new(10, 11));
+        assert_eq!(decode(r"\u{41").unwrap_err().get_span(), Span::new(6, 11));
+        assert_eq!(decode(r"\u{}").unwrap_err().get_span(), Span::new(6, 10));
+        assert_eq!(
+            decode(r"\u{1000000}").unwrap_err().message(),
+            "Overlong unicode escape"
+        );
+        assert_eq!(
+            decode(r"x\u{D800}").unwrap_err().get_span(),
+            Span::new(7, 15)
+        );
+    }
+
+    #[test]
+    fn invalid_entities() {
+        let err = decode("a &nbsp; b").unwrap_err();
+        assert_eq!(err.message(), "Unknown entity `&nbsp;`");
+        assert_eq!(err.get_span(), Span::new(8, 14));
+
+        assert_eq!(
+            decode("Tom & Jerry").unwrap_err().get_span(),
+            Span::new(10, 11)
+        );
+        assert_eq!(decode("&#xZZ;").unwrap_err().get_span(), Span::new(6, 12));
+        assert_eq!(
+            decode("&#1114112;").unwrap_err().get_span(),
+            Span::new(6, 16)
+        );
+    }
+
+    #[test]
+    fn all_errors_are_reported() {
+        let errors = decode_all(r"a\q &bogus; \u{}b&#xZZ;").unwrap_err();
+        let spans: Vec<Span> = errors.iter().map(|err| err.get_span()).collect();
+        assert_eq!(
+            spans,
+            [
+                Span::new(7, 9),
+                Span::new(10, 17),
+                Span::new(18, 22),
+                Span::new(23, 29)
+            ]
+        );
+        assert_eq!(errors[1].message(), "Unknown entity `&bogus;`");
+    }
+
+    #[test]
+    fn all_invalid_escapes() {
+        let source = SourceFile::from_string(
+            r"<program><main><println><estr>a\q &bogus;</estr></println></main></program>",
+        );
+
+        let errors = parse_ast(&source).unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|err| err.message()).collect();
+        assert_eq!(
+            messages,
+            [r"Unknown character escape `\q`", "Unknown entity `&bogus;`"]
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use std::rc::Rc;
+
+use crate::{
+    ast::{
+        AstGenerator, GeneratorOutputType, check_extra_children, is_valid_identifier, parse_body,
+        parse_identifier, parse_name_flag, parse_single_expression,
+        type_parser::parse_type_block,
+        types::{
+            ASTBlockType, CallArgument, FunctionCall, FunctionDefinition, FunctionParam, Return,
+        },
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    tokens_parser::types::UVParseNode,
+};
+
+/// Parse function definition <fn>
+pub fn parse_fn_definition(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> GeneratorOutputType {
+    check_extra_children(
+        node,
+        vec!["name", "params", "returns", "body"],
+        "Found extra children for function definition",
+    )?;
+
+    let name_block = node.get_child_by_name("name").ok_or(
+        SpannedError::new(
+            "Function definition should have an inner <name> tag",
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )?;
+
+    let body_block = node.get_child_by_name("body").ok_or(
+        SpannedError::new(
+            "Function definition should have an inner <body> tag",
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )?;
+
+    let return_type = match node.get_child_by_name("returns") {
+        Some(returns_block) => Some(parse_type_block(returns_block)?),
+        None => None,
+    };
+
+    let name = parse_identifier(name_block, "function")?;
+    let (params, body) = generator.in_function(|generator| {
+        let params = match node.get_child_by_name("params") {
+            Some(params_block) => parse_params(generator, params_block)?,
+            None => Vec::new(),
+        };
+        params
+            .iter()
+            .for_each(|param| generator.bind(&param.name.value));
+
+        Ok::<_, SpannedError>((params, parse_body(generator, body_block)?))
+    })?;
+
+    Ok(ASTBlockType::FunctionDefinition(Rc::new(
+        FunctionDefinition {
+            name,
+            params,
+            return_type,
+            body,
+            span: node.span,
+            id: generator.next_id(),
+        },
+    )))
+}
+
+/// Parse function parameters list <params>
+fn parse_params(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> Result<Vec<FunctionParam>, SpannedError> {
+    check_extra_children(
+        node,
+        vec!["arg"],
+        "Function parameters list can contain only <arg> tags",
+    )?;
+
+    let mut params: Vec<FunctionParam> = Vec::new();
+    for arg in node.get_all_tags() {
+        let param = parse_param(generator, arg)?;
+
+        if params.iter().any(|p| p.name.value == param.name.value) {
+            return Err(SpannedError::new(
+                format!("Duplicate parameter `{}`", param.name.value),
+                param.name.span,
+            )
+            .with_code(ErrorCode::Redefinition));
+        }
+        params.push(param);
+    }
+
+    Ok(params)
+}
+
+/// Parse single function parameter <arg>
+fn parse_param(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> Result<FunctionParam, SpannedError> {
+    check_extra_children(
+        node,
+        vec!["name", "type"],
+        "Found extra children for function parameter",
+    )?;
+
+    let name_block = node.get_child_by_name("name").ok_or(
+        SpannedError::new(
+            "Function parameter should have an inner <name> tag",
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )?;
+
+    let type_block = node.get_child_by_name("type").ok_or(
+        SpannedError::new(
+            "Function parameter should have an inner <type> tag",
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )?;
+
+    Ok(FunctionParam {
+        name: parse_identifier(name_block, "parameter")?,
+        param_type: parse_type_block(type_block)?,
+        span: node.span,
+        id: generator.next_id(),
+    })
+}
+
+/// Parse return statement <return>
+pub fn parse_return(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let value = match node.children_len() {
+        0 => None,
+        _ => Some(parse_single_expression(generator, node, "Return value")?),
+    };
+
+    Ok(ASTBlockType::Return(Return {
+        value,
+        span: node.span,
+    }))
+}
+
+/// Parse function call <call fn_name>
+pub fn parse_fn_call(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let callee = parse_name_flag(node)?.ok_or(
+        SpannedError::new(
+            "Function call should specify function name: <call fn_name>",
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )?;
+
+    if !is_valid_identifier(&callee.value) {
+        return Err(SpannedError::new(
+            format!("`{}` is not a valid function name", callee.value),
+            callee.span,
+        )
+        .with_code(ErrorCode::InvalidName));
+    }
+
+    if let Some(literal) = node.get_inner_literal() {
+        return Err(SpannedError::new(
+            "Function arguments must be wrapped in <arg> tag",
+            literal.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    check_extra_children(
+        node,
+        vec!["arg"],
+        "Function arguments must be wrapped in <arg> tag",
+    )?;
+
+    let mut args: Vec<CallArgument> = Vec::new();
+    for arg_block in node.get_all_tags() {
+        let arg = parse_call_argument(generator, arg_block)?;
+
+        match &arg.name {
+            None if args.iter().any(|a| a.name.is_some()) => {
+                return Err(SpannedError::new(
+                    "Positional arguments cannot follow named arguments",
+                    arg.span,
+                )
+                .with_code(ErrorCode::InvalidArguments));
+            }
+            Some(name)
+                if args
+                    .iter()
+                    .flat_map(|a| &a.name)
+                    .any(|n| n.value == name.value) =>
+            {
+                return Err(SpannedError::new(
+                    format!("Argument `{}` is passed more than once", name.value),
+                    name.span,
+                )
+                .with_code(ErrorCode::InvalidArguments));
+            }
+            _ => {}
+        }
+        args.push(arg);
+    }
+
+    Ok(ASTBlockType::FunctionCall(FunctionCall {
+        callee,
+        args,
+        span: node.span,
+        id: generator.next_id(),
+    }))
+}
+
+/// Parse single call argument `<arg>` or named `<arg name>`
+fn parse_call_argument(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> Result<CallArgument, SpannedError> {
+    let name = match parse_name_flag(node)? {
+        Some(name) if !is_valid_identifier(&name.value) => {
+            return Err(SpannedError::new(
+                format!("`{}` is not a valid argument name", name.value),
+                name.span,
+            )
+            .with_code(ErrorCode::InvalidName));
+        }
+        name => name,
+    };
+
+    Ok(CallArgument {
+        name,
+        value: parse_single_expression(generator, node, "Argument value")?,
+        span: node.span,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Diagnostics,
+        ast::{
+            gen_main_ast,
+            types::{ASTBlockType, UVType},
+        },
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+        types::Span,
+    };
+
+    fn get_program(code: &str) -> Result<ASTBlockType, Diagnostics> {
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        gen_main_ast(&node)
+    }
+
+    #[test]
+    fn function_definition() {
+        let ASTBlockType::Program(program) = get_program(
+            "<program><main><fn><name>f</name>\
+            <params><arg><name>a</name><type><int /></type></arg>\
+            <arg><name>b</name><type><union><int /><null /></union></type></arg></params>\
+            <returns><int /></returns>\
+            <body><return><a /></return></body></fn></main></program>",
+        )
+        .unwrap() else {
+            panic!("Expected program");
+        };
+
+        let ASTBlockType::MainBlock(main) = program.main else {
+            panic!("Expected main block");
+        };
+
+        let ASTBlockType::FunctionDefinition(f) = &main[0] else {
+            panic!("Expected function definition");
+        };
+
+        assert_eq!(f.name.value, "f");
+        assert_eq!(f.params.len(), 2);
+        assert_eq!(f.params[0].param_type.value, UVType::Int);
+        assert_eq!(
+            f.params[1].param_type.value,
+            UVType::Union(vec![UVType::Int, UVType::Null])
+        );
+        assert_eq!(f.return_type.as_ref().unwrap().value, UVType::Int);
+        assert!(matches!(f.body.value[0], ASTBlockType::Return(_)));
+    }
+
+    #[test]
+    fn parameter_named_b() {
+        let ASTBlockType::Program(program) = get_program(
+            "<program><main><fn><name>f</name>\
+            <params><arg><name>b</name><type><int /></type></arg></params>\
+            <body><b><int>1</int></b></body></fn><b><int>2</int></b></main></program>",
+        )
+        .unwrap() else {
+            panic!("Expected program");
+        };
+
+        let ASTBlockType::MainBlock(main) = program.main else {
+            panic!("Expected main block");
+        };
+
+        let ASTBlockType::FunctionDefinition(f) = &main[0] else {
+            panic!("Expected function definition");
+        };
+        assert!(matches!(
+            f.body.value[0],
+            ASTBlockType::VariableAssignment(_)
+        ));
+        assert!(matches!(main[1], ASTBlockType::GroupBlock(_)));
+    }
+
+    #[test]
+    fn function_in_head() {
+        assert!(
+            get_program(
+                "<program><head><fn><name>f</name><body></body></fn></head><main></main></program>"
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn nested_function() {
+        assert!(
+            get_program(
+                "<program><main><while><cond><bool>1</bool></cond><body>\
+                <fn><name>f</name><body></body></fn></body></while></main></program>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn duplicate_parameter() {
+        assert!(
+            get_program(
+                "<program><main><fn><name>f</name><params>\
+                <arg><name>a</name><type><int /></type></arg>\
+                <arg><name>a</name><type><int /></type></arg>\
+                </params><body></body></fn></main></program>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parameter_without_type() {
+        assert!(
+            get_program(
+                "<program><main><fn><name>f</name><params><arg><name>a</name></arg></params>\
+                <body></body></fn></main></program>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn function_call() {
+        let ASTBlockType::Program(program) = get_program(
+            "<program><main><call f><arg><int>1</int></arg>\
+            <arg b><str>x</str></arg></call></main></program>",
+        )
+        .unwrap() else {
+            panic!("Expected program");
+        };
+
+        let ASTBlockType::MainBlock(main) = program.main else {
+            panic!("Expected main block");
+        };
+
+        let ASTBlockType::FunctionCall(call) = &main[0] else {
+            panic!("Expected function call");
+        };
+
+        assert_eq!(call.callee.value, "f");
+        assert_eq!(call.callee.span, Span::new(21, 22));
+        assert_eq!(call.args.len(), 2);
+        assert!(call.args[0].name.is_none());
+        assert_eq!(call.args[0].span, Span::new(23, 46));
+        assert_eq!(call.args[1].name.as_ref().unwrap().value, "b");
+        assert_eq!(call.args[1].name.as_ref().unwrap().span, Span::new(51, 52));
+        assert_eq!(call.args[1].span, Span::new(46, 71));
+    }
+
+    #[test]
+    fn call_without_name() {
+        assert!(get_program("<program><main><call></call></main></program>").is_err());
+    }
+
+    #[test]
+    fn call_with_unwrapped_argument() {
+        assert!(
+            get_program("<program><main><call f><int>1</int></call></main></program>").is_err()
+        );
+    }
+
+    #[test]
+    fn positional_after_named() {
+        assert!(
+            get_program(
+                "<program><main><call f><arg a><int>1</int></arg>                <arg><int>2</int></arg></call></main></program>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn keyword_is_not_assignment() {
+        assert!(get_program("<program><main><let><int>1</int></let></main></program>").is_err());
+        assert!(
+            get_program(
+                "<program><main><let><name>call</name><value><int>1</int></value></let>                </main></program>"
+            )
+            .is_err()
+        );
+    }
+}
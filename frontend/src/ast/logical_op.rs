@@ -1,22 +1,25 @@
 use crate::{
     ast::{
-        GeneratorOutputType, generate_ast,
+        AstGenerator, GeneratorOutputType,
+        operands::parse_operands,
         traits::{ArgumentsCount, StringToUVLogicalOp},
         types::{ASTBlockType, LogicalOp},
     },
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     tokens_parser::types::UVParseNode,
 };
 
 /// Parse Ultraviolet logical operators
-pub fn parse_logical_op(node: &UVParseNode) -> GeneratorOutputType {
-    let op_type = node
-        .name
-        .to_uvlogical()
-        .ok_or(SpannedError::new("Unknown logical operation", node.span))?;
+pub fn parse_logical_op(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let op_type = node.name.to_uvlogical().ok_or(
+        SpannedError::new("Unknown logical operation", node.span)
+            .with_code(ErrorCode::UnexpectedTag),
+    )?;
 
-    let children = parse_arguments(
+    let children = parse_operands(
+        generator,
         node,
+        "logical operation",
         op_type.min_arguments_count(),
         op_type.max_arguments_count(),
     )?;
@@ -27,41 +30,3 @@ pub fn parse_logical_op(node: &UVParseNode) -> GeneratorOutputType {
         span: node.span,
     }))
 }
-
-/// Parse arguments for logical op
-fn parse_arguments(
-    node: &UVParseNode,
-    min: usize,
-    max: Option<usize>,
-) -> Result<Vec<ASTBlockType>, SpannedError> {
-    if !node.all_tags() {
-        return Err(SpannedError::new(
-            "Unexpected literals inside logical operation",
-            node.span,
-        ));
-    }
-
-    if node.children_len() < min {
-        return Err(SpannedError::new(
-            format!("Comparison operator cannot have less than {min} operands"),
-            node.span,
-        ));
-    }
-
-    if let Some(m) = max
-        && node.children_len() > m
-    {
-        return Err(SpannedError::new(
-            format!(
-                "`{}` logical operation can handle only {} arguments",
-                node.name, m
-            ),
-            node.span,
-        ));
-    }
-
-    node.get_all_tags()
-        .into_iter()
-        .map(|ch| generate_ast(ch))
-        .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
-}
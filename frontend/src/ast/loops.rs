@@ -1,21 +1,200 @@
 use crate::{
-    ast::GeneratorOutputType, errors::SpannedError, tokens_parser::types::UVParseNode,
-    types::Positional,
+    ast::{
+        AstGenerator, GeneratorOutputType, check_extra_children, get_optional_child,
+        get_single_child,
+        operands::parse_condition,
+        parse_body, parse_identifier, parse_single_expression,
+        types::{ASTBlockType, ForLoop, WhileLoop},
+    },
+    tokens_parser::types::UVParseNode,
 };
 
-pub fn parse_for_loop(node: &UVParseNode) -> GeneratorOutputType {
-    let extra = node.search_extra_children(vec!["iterator", "start", "end", "step", "body"]);
+/// Parse numeric for loop <for>
+pub fn parse_for_loop(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    check_extra_children(
+        node,
+        vec!["iterator", "start", "end", "step", "body"],
+        "Found extra children inside `for` loop declaration",
+    )?;
 
-    if !extra.is_empty() {
-        let first_extra = extra.first().ok_or(SpannedError::new(
-            "[INTERNAL ERROR] Cannot get inner extra tag",
-            node.span,
-        ))?;
+    let owner = "For loop";
+    let iterator_block = get_single_child(node, "iterator", owner)?;
+    let start_block = get_single_child(node, "start", owner)?;
+    let end_block = get_single_child(node, "end", owner)?;
+    let body_block = get_single_child(node, "body", owner)?;
 
-        return Err(SpannedError::new(
-            "Found extra children inside `for` loop declaration",
-            first_extra.get_span(),
+    let step = match get_optional_child(node, "step", owner)? {
+        Some(step_block) => Some(parse_single_expression(generator, step_block, "Loop step")?),
+        None => None,
+    };
+
+    let iterator = parse_identifier(iterator_block, "iterator")?;
+    let start = parse_single_expression(generator, start_block, "Loop start")?;
+    let end = parse_single_expression(generator, end_block, "Loop end")?;
+    let body = generator.in_scope(|generator| {
+        generator.bind(&iterator.value);
+        parse_body(generator, body_block)
+    })?;
+
+    Ok(ASTBlockType::ForLoop(ForLoop {
+        iterator,
+        start,
+        end,
+        step,
+        body,
+        span: node.span,
+        id: generator.next_id(),
+    }))
+}
+
+/// Parse while loop <while>
+pub fn parse_while_loop(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    check_extra_children(
+        node,
+        vec!["cond", "body"],
+        "Found extra children inside `while` loop declaration",
+    )?;
+
+    let cond_block = get_single_child(node, "cond", "While loop")?;
+    let body_block = get_single_child(node, "body", "While loop")?;
+
+    Ok(ASTBlockType::WhileLoop(WhileLoop {
+        condition: parse_condition(generator, cond_block)?,
+        body: parse_body(generator, body_block)?,
+        span: node.span,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{AstGenerator, GeneratorOutputType, generate_ast, types::ASTBlockType},
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+        types::{Positional, Span},
+    };
+
+    fn get_ast(code: &str) -> GeneratorOutputType {
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        generate_ast(&mut AstGenerator::default(), &node)
+    }
+
+    #[test]
+    fn for_loop() {
+        let ASTBlockType::ForLoop(for_loop) = get_ast(
+            "<for><iterator>i</iterator><start><int>0</int></start>\
+            <end><int>10</int></end><body><i /></body></for>",
+        )
+        .unwrap() else {
+            panic!("Expected for loop");
+        };
+
+        assert_eq!(for_loop.iterator.value, "i");
+        assert_eq!(for_loop.iterator.span, Span::new(5, 27));
+        assert!(for_loop.step.is_none());
+        assert_eq!(for_loop.body.value.len(), 1);
+    }
+
+    #[test]
+    fn for_loop_with_step() {
+        let ASTBlockType::ForLoop(for_loop) = get_ast(
+            "<for><iterator>i</iterator><start><int>0</int></start>\
+            <end><int>10</int></end><step><int>2</int></step><body></body></for>",
+        )
+        .unwrap() else {
+            panic!("Expected for loop");
+        };
+
+        assert!(for_loop.step.is_some());
+        assert!(for_loop.body.value.is_empty());
+    }
+
+    #[test]
+    fn for_loop_missing_end() {
+        assert!(
+            get_ast("<for><iterator>i</iterator><start><int>0</int></start><body></body></for>")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn while_loop_duplicate_child() {
+        let error = get_ast(
+            "<while><cond><bool>1</bool></cond><cond><bool>0</bool></cond><body></body></while>",
+        )
+        .unwrap_err();
+        assert_eq!(error.message(), "While loop can have only one <cond> tag");
+        assert_eq!(error.get_span(), Span::new(34, 61));
+    }
+
+    #[test]
+    fn for_loop_duplicate_child() {
+        let error = get_ast(
+            "<for><iterator>i</iterator><start><int>0</int></start>\
+            <end><int>10</int></end><end><int>20</int></end><body></body></for>",
+        )
+        .unwrap_err();
+        assert_eq!(error.message(), "For loop can have only one <end> tag");
+        assert_eq!(error.get_span(), Span::new(78, 102));
+    }
+
+    #[test]
+    fn for_loop_extra_child() {
+        assert!(
+            get_ast(
+                "<for><iterator>i</iterator><start><int>0</int></start>\
+                <end><int>10</int></end><body></body><ss/></for>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn for_loop_invalid_iterator() {
+        assert!(
+            get_ast(
+                "<for><iterator>1i</iterator><start><int>0</int></start>\
+                <end><int>10</int></end><body></body></for>"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn while_loop() {
+        let ASTBlockType::WhileLoop(while_loop) = get_ast(
+            "<while><cond><lt><i /><int>10</int></lt></cond><body><i /><i /></body></while>",
+        )
+        .unwrap() else {
+            panic!("Expected while loop");
+        };
+
+        assert!(matches!(
+            *while_loop.condition.value,
+            ASTBlockType::CompareOp(_)
         ));
+        assert_eq!(while_loop.body.value.len(), 2);
+    }
+
+    #[test]
+    fn while_loop_missing_cond() {
+        assert!(get_ast("<while><body></body></while>").is_err());
+    }
+
+    #[test]
+    fn while_loop_multiple_conditions() {
+        assert!(
+            get_ast("<while><cond><bool>1</bool><bool>0</bool></cond><body></body></while>")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn while_loop_extra_child() {
+        assert!(
+            get_ast("<while><cond><bool>1</bool></cond><body></body><test /></while>").is_err()
+        );
     }
-    todo!()
 }
@@ -1,20 +1,26 @@
 use crate::{
     ast::{
-        GeneratorOutputType, generate_ast,
+        AstGenerator, GeneratorOutputType,
+        operands::parse_operands,
         traits::{IsVariadic, StringToUVMathOp},
         types::{ASTBlockType, MathOp},
     },
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     tokens_parser::types::UVParseNode,
 };
 
-pub fn parse_math_op(node: &UVParseNode) -> GeneratorOutputType {
-    let op_type = node
-        .name
-        .to_uvmath()
-        .ok_or(SpannedError::new("Unknown math operation", node.span))?;
+/// Parse Ultraviolet math operations
+pub fn parse_math_op(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let op_type = node.name.to_uvmath().ok_or(
+        SpannedError::new("Unknown math operation", node.span).with_code(ErrorCode::UnexpectedTag),
+    )?;
 
-    let children = parse_arguments(node, !op_type.is_variadic())?;
+    let (min, max) = if op_type.is_variadic() {
+        (0, None)
+    } else {
+        (2, Some(2))
+    };
+    let children = parse_operands(generator, node, "math operation", min, max)?;
 
     Ok(ASTBlockType::MathOp(MathOp {
         op_type,
@@ -22,28 +28,3 @@ pub fn parse_math_op(node: &UVParseNode) -> GeneratorOutputType {
         span: node.span,
     }))
 }
-
-/// Parse arguments for math functions
-pub fn parse_arguments(
-    node: &UVParseNode,
-    only_two: bool,
-) -> Result<Vec<ASTBlockType>, SpannedError> {
-    if !node.all_tags() {
-        return Err(SpannedError::new(
-            "Unexpected literals inside math operation",
-            node.span,
-        ));
-    }
-
-    if only_two && node.children_len() != 2 {
-        return Err(SpannedError::new(
-            format!("`{}` math operation can handle only 2 arguments", node.name),
-            node.span,
-        ));
-    }
-
-    node.get_all_tags()
-        .into_iter()
-        .map(|ch| generate_ast(ch))
-        .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
-}
@@ -2,29 +2,39 @@ use anyhow::Result;
 use regex::Regex;
 
 use crate::{
+    Diagnostics,
     ast::{
         compare_op::parse_compare_op,
+        conditional::parse_conditional_op,
+        functions::{parse_fn_call, parse_fn_definition, parse_return},
         logical_op::parse_logical_op,
-        loops::parse_for_loop,
+        loops::{parse_for_loop, parse_while_loop},
         math_op::parse_math_op,
+        operands::parse_operands,
         traits::{StringToUVCompareOp, StringToUVLogicalOp, StringToUVMathOp, StringToUVType},
         type_parser::parse_type,
-        types::{ASTBlockType, ProgramBlock},
-        values::parse_value,
+        types::{ASTBlockType, GroupBlock, NodeId, Print, ProgramBlock},
+        values::{check_escapes, parse_value},
         variables::{parse_var_access, parse_var_assign, parse_var_definition},
     },
-    errors::SpannedError,
-    tokens_parser::types::UVParseNode,
+    errors::{Severity, SpannedError, codes::ErrorCode},
+    tokens_parser::types::{UVAttribute, UVParseBody, UVParseNode},
+    types::{Positional, Spanned},
 };
+use colored::Colorize;
 use once_cell::sync::Lazy;
 
 mod compare_op;
+mod conditional;
+mod escapes;
+mod functions;
 mod logical_op;
 mod loops;
 mod math_op;
-mod traits;
+mod operands;
+pub mod traits;
 mod type_parser;
-mod types;
+pub mod types;
 mod values;
 mod variables;
 
@@ -32,96 +42,508 @@ pub type GeneratorOutputType = Result<ASTBlockType, SpannedError>;
 
 static IDENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_]*$").unwrap());
 
+/// Keyword tags of `generate_ast`, that would otherwise fall through to variable access or
+/// assignment. Built-in type and operator tags stay valid identifiers, as before
+const RESERVED_TAGS: [&str; 10] = [
+    "let", "fn", "return", "call", "if", "for", "while", "union", "println", "estr",
+];
+
 /// Check if provided string is a valid var/fn identifier
 fn is_valid_identifier(s: &str) -> bool {
-    IDENT_REGEX.is_match(s)
+    IDENT_REGEX.is_match(s) && !RESERVED_TAGS.contains(&s)
+}
+
+/// State of the AST generation, that is shared by all parsed nodes.
+///
+/// Each program is generated with a new generator, while REPL keeps one generator
+/// for all entries, so node ids stay unique in the whole session
+#[derive(Debug, Default)]
+pub struct AstGenerator {
+    next_id: usize,
+
+    /// Variable names bound by the enclosing blocks. The first scope holds top-level names.
+    /// `<b>` is a group block only when `b` is not bound, otherwise it is an assignment
+    scopes: Vec<Vec<String>>,
 }
 
-/// Parse <program> content
-pub fn gen_main_ast(node: &UVParseNode) -> GeneratorOutputType {
+impl AstGenerator {
+    /// Get id for the new node, that was never returned by this generator before
+    fn next_id(&mut self) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Bind variable name in the current scope
+    fn bind(&mut self, name: &str) {
+        match self.scopes.last_mut() {
+            Some(scope) => scope.push(name.to_owned()),
+            None => self.scopes.push(vec![name.to_owned()]),
+        }
+    }
+
+    /// Check if variable name is bound in any enclosing scope
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().flatten().any(|bound| bound == name)
+    }
+
+    /// Run `parse` in a nested scope, so names bound inside are forgotten after it
+    fn in_scope<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push(Vec::new());
+        let result = parse(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// Run `parse` in a function scope, that cannot see variables of the outer code
+    fn in_function<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = std::mem::replace(&mut self.scopes, vec![Vec::new()]);
+        let result = parse(self);
+        self.scopes = outer;
+        result
+    }
+
+    /// Count of top-level names, that can be passed to `rollback` later
+    pub fn checkpoint(&self) -> usize {
+        self.scopes.first().map_or(0, Vec::len)
+    }
+
+    /// Forget top-level names bound after the checkpoint (e.g. by a rejected REPL entry)
+    pub fn rollback(&mut self, checkpoint: usize) {
+        if let Some(scope) = self.scopes.first_mut() {
+            scope.truncate(checkpoint);
+        }
+    }
+}
+
+/// Parse <program> content. Errors of all statements are collected
+pub fn gen_main_ast(node: &UVParseNode) -> Result<ASTBlockType, Diagnostics> {
+    let generator = &mut AstGenerator::default();
+
     if node.name.ne("program") {
-        return Err(SpannedError::new(
-            "The program must begin with the <program> tag",
-            node.span,
-        ));
+        return Err(vec![
+            SpannedError::new("The program must begin with the <program> tag", node.span)
+                .with_code(ErrorCode::InvalidProgramStructure),
+        ]);
     }
 
-    let head_parsed = if let Some(h) = node.get_child_by_name("head") {
-        Some(ASTBlockType::HeadBlock(parse_root_children(&h)?))
-    } else {
-        None
+    let mut errors = Vec::new();
+
+    let head_parsed = node
+        .get_child_by_name("head")
+        .map(|h| ASTBlockType::HeadBlock(parse_root_children(generator, h, &mut errors)));
+
+    let main = match node.get_child_by_name("main") {
+        Some(m) => ASTBlockType::MainBlock(parse_root_children(generator, m, &mut errors)),
+        // Main block may be lost because of parse error, that is already reported
+        None if node.has_errors() => return Err(errors),
+        None => {
+            errors.push(
+                SpannedError::new("Main block in <program> is required", node.span)
+                    .with_code(ErrorCode::InvalidProgramStructure),
+            );
+            return Err(errors);
+        }
     };
 
-    let main =
-        ASTBlockType::MainBlock(parse_root_children(node.get_child_by_name("main").ok_or(
-            SpannedError::new("Main block in <program> is required", node.span),
-        )?)?);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
 
     Ok(ASTBlockType::Program(Box::new(ProgramBlock {
         head: head_parsed,
-        main: main,
+        main,
         span: node.span,
     })))
 }
 
+/// Language versions, that can be requested with `<?uv version="..."?>`
+const UV_VERSIONS: [&str; 2] = ["1", "1.0"];
+
+/// Get warnings for processing instructions of the root tag, that are ignored
+pub fn check_instructions(node: &UVParseNode) -> Diagnostics {
+    let mut warnings = Vec::new();
+
+    for instruction in &node.instructions {
+        if instruction.target.value != "uv" {
+            warnings.push(
+                SpannedError::new(
+                    format!(
+                        "Unknown processing instruction `{}`",
+                        instruction.target.value
+                    ),
+                    instruction.target.span,
+                )
+                .with_code(ErrorCode::UnknownInstruction)
+                .with_severity(Severity::Warning)
+                .with_note("the instruction is ignored"),
+            );
+            continue;
+        }
+
+        if let Some(version) = instruction.get_attribute_value("version")
+            && !UV_VERSIONS.contains(&version.value.as_str())
+        {
+            warnings.push(
+                SpannedError::new(
+                    format!("Unsupported language version `{}`", version.value),
+                    version.span,
+                )
+                .with_code(ErrorCode::UnknownInstruction)
+                .with_severity(Severity::Warning)
+                .with_help(format!("supported versions: {}", UV_VERSIONS.join(", "))),
+            );
+        }
+    }
+
+    warnings
+}
+
 /// Main recursively invoked parsing function
-pub fn generate_ast(node: &UVParseNode) -> GeneratorOutputType {
+pub fn generate_ast(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
     Ok(match node.name.as_str() {
         // Parse variable declaration
-        "let" if !node.self_closing => parse_var_definition(node)?,
+        "let" if !node.self_closing => parse_var_definition(generator, node)?,
 
         // Parse for loop declaration
-        "for" if !node.self_closing => parse_for_loop(node)?,
+        "for" if !node.self_closing => parse_for_loop(generator, node)?,
+
+        // Parse while loop declaration
+        "while" if !node.self_closing => parse_while_loop(generator, node)?,
+
+        // Parse conditional operator
+        "if" if !node.self_closing => parse_conditional_op(generator, node)?,
+
+        // Functions can be defined only on the top level (see `parse_root_children`)
+        "fn" => {
+            return Err(SpannedError::new(
+                "Functions can be defined only inside <head> or <main>",
+                node.span,
+            )
+            .with_code(ErrorCode::InvalidProgramStructure));
+        }
+
+        // Parse return statement
+        "return" => parse_return(generator, node)?,
+
+        // Parse function call
+        "call" if !node.self_closing => parse_fn_call(generator, node)?,
+
+        // Parse group block. Bound `b` variable is assigned instead (see below)
+        "b" if !node.self_closing && !generator.is_bound("b") => {
+            parse_group_block(generator, node)?
+        }
+
+        // Parse built-in print
+        "println" if !node.self_closing => parse_println(generator, node)?,
 
         // Type parsing
         // FIXME: Parsing of types should only occur in special places
         // TODO: Move this parsing to a separate function
-        name if name.to_uvtype().is_some() && node.self_closing => parse_type(node)?,
+        name if name.to_uvtype().is_some() && node.self_closing && !node.has_attribute("value") => {
+            parse_type(node)?
+        }
         "union" if !node.self_closing => parse_type(node)?,
 
         // Values such as int, float, etc.
         name if name.to_uvtype().is_some() => parse_value(node)?,
+        "estr" if !node.self_closing => parse_value(node)?,
 
         // Parse math operations, such as sum, div, etc.
-        name if name.to_uvmath().is_some() && !node.self_closing => parse_math_op(node)?,
+        name if name.to_uvmath().is_some() && !node.self_closing => parse_math_op(generator, node)?,
 
         // Parse compare operators, such as eq, neq, etc.
-        name if name.to_uvcompare().is_some() && !node.self_closing => parse_compare_op(node)?,
+        name if name.to_uvcompare().is_some() && !node.self_closing => {
+            parse_compare_op(generator, node)?
+        }
 
         // Parse logical operators, such as and, or, not
-        name if name.to_uvlogical().is_some() && !node.self_closing => parse_logical_op(node)?,
-
-        // Parse variable assign
-        _ if !node.self_closing => parse_var_assign(node)?,
+        name if name.to_uvlogical().is_some() && !node.self_closing => {
+            parse_logical_op(generator, node)?
+        }
 
-        // Parse variable access
-        _ if node.self_closing => parse_var_access(node)?,
+        // Any other tag must be a variable. Keywords are excluded by `is_valid_identifier`,
+        // so misused `call`, `let`, etc. never fall through to variable assignment
+        name if is_valid_identifier(name) && node.self_closing => {
+            parse_var_access(generator, node)?
+        }
+        name if is_valid_identifier(name) => parse_var_assign(generator, node)?,
 
         name => {
-            return Err(SpannedError::new(
-                format!("Unexpected `{name}` tag"),
-                node.span,
-            ));
+            return Err(
+                SpannedError::new(format!("Unexpected `{name}` tag"), node.span)
+                    .with_code(ErrorCode::UnexpectedTag),
+            );
         }
     })
 }
 
-/// Parse children in head and main tags
-fn parse_root_children(node: &UVParseNode) -> Result<Vec<ASTBlockType>, SpannedError> {
-    if !node.all_tags() {
-        let first_literal = node.get_inner_literal().ok_or(SpannedError::new(
-            "[INTERNAL ERROR] Cannot get inner literal for error",
+/// Parse children in head and main tags. Errors are collected to `errors`
+fn parse_root_children(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+    errors: &mut Diagnostics,
+) -> Vec<ASTBlockType> {
+    if let Some(literal) = node.get_inner_literal() {
+        errors.push(
+            SpannedError::new("Unexpected unwrapped literal in root tag", literal.span)
+                .with_code(ErrorCode::InvalidProgramStructure),
+        );
+    }
+
+    parse_root_statements(generator, node.get_all_tags(), errors)
+}
+
+/// Parse top-level statements, that are not wrapped in <program> (e.g. REPL input)
+pub fn gen_statements_ast(
+    generator: &mut AstGenerator,
+    nodes: &[UVParseNode],
+) -> Result<Vec<ASTBlockType>, Diagnostics> {
+    let mut errors = Vec::new();
+    let statements = parse_root_statements(generator, nodes, &mut errors);
+
+    match errors.is_empty() {
+        true => Ok(statements),
+        false => Err(errors),
+    }
+}
+
+/**
+Parse each statement independently, so one bad statement does not hide errors in the others
+
+Statements damaged by parse errors are skipped, their errors are already reported
+*/
+fn parse_root_statements<'a>(
+    generator: &mut AstGenerator,
+    nodes: impl IntoIterator<Item = &'a UVParseNode>,
+    errors: &mut Diagnostics,
+) -> Vec<ASTBlockType> {
+    nodes
+        .into_iter()
+        .filter(|node| !node.has_errors())
+        .filter_map(|node| {
+            // Generator stops at the first error, so all invalid escapes are collected before
+            let errors_count = errors.len();
+            check_escapes(node, errors);
+            if errors.len() > errors_count {
+                return None;
+            }
+
+            parse_root_statement(generator, node)
+                .map_err(|err| errors.push(err))
+                .ok()
+        })
+        .collect()
+}
+
+/// Parse statement of the root tag. Only here functions can be defined
+fn parse_root_statement(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    match node.name.as_str() {
+        "fn" if !node.self_closing => parse_fn_definition(generator, node),
+        _ => generate_ast(generator, node),
+    }
+}
+
+/// Parse group block <b>
+fn parse_group_block(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    let body = parse_body(generator, node)?;
+
+    Ok(ASTBlockType::GroupBlock(GroupBlock {
+        statements: body.value,
+        span: node.span,
+    }))
+}
+
+/// Parse built-in print <println>
+fn parse_println(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
+    Ok(ASTBlockType::Print(Print {
+        operands: parse_operands(generator, node, "print", 0, None)?,
+        span: node.span,
+    }))
+}
+
+/// Parse statements of a body-like tag (`<body>`, `<then>`, etc.)
+fn parse_body(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> Result<Spanned<Vec<ASTBlockType>>, SpannedError> {
+    if let Some(literal) = node.get_inner_literal() {
+        return Err(SpannedError::new(
+            format!("Unexpected unwrapped literal inside <{}> tag", node.name),
+            literal.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    let statements = generator.in_scope(|generator| {
+        node.get_all_tags()
+            .into_iter()
+            .map(|node| generate_ast(generator, node))
+            .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
+    })?;
+
+    Ok(Spanned::new(statements, node.span))
+}
+
+/// Parse wrapper tag (such as `<value>` or `<start>`) that holds exactly one expression.
+/// `what` describes the content in errors, e.g. "Variable value"
+fn parse_single_expression(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+    what: &str,
+) -> Result<Spanned<Box<ASTBlockType>>, SpannedError> {
+    if node.children_len() != 1 || !node.all_tags() {
+        return Err(SpannedError::new(
+            format!(
+                "{what} must have only one inner tag.\n{}{}",
+                "tip".green(),
+                ": If you want to place multiple tags, wrap them in a <b> tag.",
+            ),
             node.span,
-        ))?;
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    let inner = node.get_tag_at(0).ok_or(SpannedError::new(
+        "[INTERNAL ERROR] Cannot get inner tag",
+        node.span,
+    ))?;
+
+    Ok(Spanned::new(
+        Box::new(generate_ast(generator, inner)?),
+        node.span,
+    ))
+}
+
+/// Parse name tag (such as `<name>` or `<iterator>`) that holds a single identifier literal
+fn parse_identifier(node: &UVParseNode, kind: &str) -> Result<Spanned<String>, SpannedError> {
+    if node.children_len() != 1 || !node.all_literals() {
+        return Err(SpannedError::new(format!("Invalid {kind} name"), node.span)
+            .with_code(ErrorCode::InvalidName));
+    }
+
+    let name = node.get_inner_literal().ok_or(SpannedError::new(
+        "[INTERNAL ERROR] Cannot get inner literal",
+        node.span,
+    ))?;
 
+    validate_identifier(name, kind)?;
+    Ok(Spanned::new(name.value.clone(), node.span))
+}
+
+/// Parse name attribute (such as `name="x"`) that holds a single identifier
+fn parse_identifier_attribute(
+    attr: &UVAttribute,
+    kind: &str,
+) -> Result<Spanned<String>, SpannedError> {
+    let name = attr.value.as_ref().ok_or(
+        SpannedError::new(
+            format!("`{}` attribute must have a value", attr.key.value),
+            attr.span,
+        )
+        .with_code(ErrorCode::InvalidAttribute)
+        .with_help(format!(
+            "write {kind} name in quotes: {}=\"...\"",
+            attr.key.value
+        )),
+    )?;
+
+    validate_identifier(name, kind)?;
+    Ok(Spanned::new(name.value.clone(), attr.span))
+}
+
+fn validate_identifier(name: &Spanned<String>, kind: &str) -> Result<(), SpannedError> {
+    if !is_valid_identifier(&name.value) {
         return Err(SpannedError::new(
-            "Unexpected unwrapped literal in root tag",
-            first_literal.span,
-        ));
+            format!("`{}` is not a valid name for {kind}", name.value),
+            name.span,
+        )
+        .with_code(ErrorCode::InvalidName));
     }
+    Ok(())
+}
 
-    node.get_all_tags()
+/// Get name, that is written as the only bare attribute, such as `<call fn_name>`
+fn parse_name_flag(node: &UVParseNode) -> Result<Option<Spanned<String>>, SpannedError> {
+    match node.attributes.as_slice() {
+        [] => Ok(None),
+        [name] if name.value.is_none() => Ok(Some(name.key.clone())),
+        [name] => Err(unexpected_attribute(node, name)),
+        [_, extra, ..] => Err(unexpected_attribute(node, extra)),
+    }
+}
+
+/// Return error for the first attribute, that not included in white list
+fn check_extra_attributes(node: &UVParseNode, white_list: &[&str]) -> Result<(), SpannedError> {
+    match node
+        .attributes
         .iter()
-        .map(|ch| Ok(generate_ast(ch)?))
-        .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
+        .find(|attr| !white_list.contains(&attr.key.value.as_str()))
+    {
+        Some(extra) => Err(unexpected_attribute(node, extra)),
+        None => Ok(()),
+    }
+}
+
+fn unexpected_attribute(node: &UVParseNode, attr: &UVAttribute) -> SpannedError {
+    SpannedError::new(
+        format!(
+            "Unexpected attribute `{}` for <{}> tag",
+            attr.key.value, node.name
+        ),
+        attr.span,
+    )
+    .with_code(ErrorCode::InvalidAttribute)
+}
+
+/// Get child tag, that must appear exactly once inside of `owner`
+fn get_single_child<'a>(
+    node: &'a UVParseNode,
+    name: &str,
+    owner: &str,
+) -> Result<&'a UVParseNode, SpannedError> {
+    get_optional_child(node, name, owner)?.ok_or(
+        SpannedError::new(
+            format!("{owner} should have an inner <{name}> tag"),
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent),
+    )
+}
+
+/// Get child tag, that can appear at most once inside of `owner`
+fn get_optional_child<'a>(
+    node: &'a UVParseNode,
+    name: &str,
+    owner: &str,
+) -> Result<Option<&'a UVParseNode>, SpannedError> {
+    let mut found = node.children.iter().filter_map(|ch| match ch {
+        UVParseBody::Tag(tag) if tag.name == name => Some(tag.as_ref()),
+        _ => None,
+    });
+
+    let first = found.next();
+    if let Some(duplicate) = found.next() {
+        return Err(SpannedError::new(
+            format!("{owner} can have only one <{name}> tag"),
+            duplicate.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    Ok(first)
+}
+
+/// Return error for the first child, that not included in white list
+fn check_extra_children(
+    node: &UVParseNode,
+    white_list: Vec<&str>,
+    message: &str,
+) -> Result<(), SpannedError> {
+    match node.search_extra_children(white_list).first() {
+        Some(extra) => {
+            Err(SpannedError::new(message, extra.get_span()).with_code(ErrorCode::UnexpectedTag))
+        }
+        None => Ok(()),
+    }
 }
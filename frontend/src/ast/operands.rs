@@ -0,0 +1,65 @@
+use crate::{
+    ast::{AstGenerator, generate_ast, types::ASTBlockType},
+    errors::{SpannedError, codes::ErrorCode},
+    tokens_parser::types::UVParseNode,
+    types::Spanned,
+};
+
+/// Parse operands of an operation tag and check their count.
+///
+/// `kind` is a human-readable operation kind used in error messages
+pub fn parse_operands(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+    kind: &str,
+    min: usize,
+    max: Option<usize>,
+) -> Result<Vec<ASTBlockType>, SpannedError> {
+    if let Some(literal) = node.get_inner_literal() {
+        return Err(
+            SpannedError::new(format!("Unexpected literals inside {kind}"), literal.span)
+                .with_code(ErrorCode::InvalidTagContent),
+        );
+    }
+
+    if node.children_len() < min {
+        return Err(SpannedError::new(
+            format!(
+                "`{}` {kind} cannot have less than {min} operands",
+                node.name
+            ),
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    if let Some(m) = max
+        && node.children_len() > m
+    {
+        return Err(SpannedError::new(
+            format!("`{}` {kind} can handle only {m} arguments", node.name),
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidTagContent));
+    }
+
+    node.get_all_tags()
+        .into_iter()
+        .map(|node| generate_ast(generator, node))
+        .collect::<Result<Vec<ASTBlockType>, SpannedError>>()
+}
+
+/// Parse condition tag <cond> that holds exactly one operand
+pub fn parse_condition(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> Result<Spanned<Box<ASTBlockType>>, SpannedError> {
+    let operand = parse_operands(generator, node, "condition", 1, Some(1))?
+        .pop()
+        .ok_or(SpannedError::new(
+            "[INTERNAL ERROR] Cannot get condition operand",
+            node.span,
+        ))?;
+
+    Ok(Spanned::new(Box::new(operand), node.span))
+}
@@ -1,4 +1,7 @@
-use crate::ast::types::{CompareOpType, LogicalOpType, MathOpType, UVType};
+use crate::{
+    analyzer::scope::SymbolTable,
+    ast::types::{CompareOpType, LogicalOpType, MathOpType, UVType},
+};
 
 pub trait GetType {
     /// Get type of node / value
@@ -6,9 +9,8 @@ pub trait GetType {
 }
 
 pub trait GetTypeScope {
-    /// Get symbol type from scope
-    /// TODO: Replace scope with real scope type
-    fn get_type_from_scope(&self, scope: Option<usize>) -> UVType;
+    /// Get type of node, that refers to a symbol (e.g. variable access)
+    fn get_type_from_scope(&self, symbols: &SymbolTable) -> Option<UVType>;
 }
 
 pub trait IsAssignable {
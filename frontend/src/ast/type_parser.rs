@@ -1,33 +1,40 @@
 use crate::{
     ast::{
-        GeneratorOutputType,
+        GeneratorOutputType, check_extra_attributes,
         types::{ASTBlockType, UVType},
     },
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     tokens_parser::types::UVParseNode,
+    types::Spanned,
 };
 
 /// Parse Ultraviolet type
 pub fn parse_type(node: &UVParseNode) -> GeneratorOutputType {
-    Ok(ASTBlockType::Type(parse(node)?))
+    Ok(ASTBlockType::Type(Spanned::new(
+        parse_uvtype(node)?,
+        node.span,
+    )))
 }
 
-fn parse(node: &UVParseNode) -> Result<UVType, SpannedError> {
+/// Parse type tag (such as `<int />` or `<union>`) to a UVType
+pub fn parse_uvtype(node: &UVParseNode) -> Result<UVType, SpannedError> {
+    check_extra_attributes(node, &[])?;
+
     if node.name.eq("union") {
         if node.self_closing {
-            return Err(SpannedError::new(
-                "Union cannot be used as individual type",
-                node.span,
-            ));
+            return Err(
+                SpannedError::new("Union cannot be used as individual type", node.span)
+                    .with_code(ErrorCode::InvalidType),
+            );
         }
         return parse_union(node);
     }
 
     if !node.self_closing {
-        return Err(SpannedError::new(
-            "All type tags must be self-closing",
-            node.span,
-        ));
+        return Err(
+            SpannedError::new("All type tags must be self-closing", node.span)
+                .with_code(ErrorCode::InvalidType),
+        );
     }
 
     Ok(match node.name.as_str() {
@@ -37,24 +44,44 @@ fn parse(node: &UVParseNode) -> Result<UVType, SpannedError> {
         "bool" => UVType::Boolean,
         "null" => UVType::Null,
         _ => {
-            return Err(SpannedError::new(
-                format!("Unknown type `{}`", node.name),
-                node.span,
-            ));
+            return Err(
+                SpannedError::new(format!("Unknown type `{}`", node.name), node.span)
+                    .with_code(ErrorCode::InvalidType),
+            );
         }
     })
 }
 
+/// Parse wrapper tag (such as `<type>` or `<returns>`) that holds exactly one type
+pub fn parse_type_block(node: &UVParseNode) -> Result<Spanned<UVType>, SpannedError> {
+    if node.children_len() != 1 || !node.all_tags() {
+        return Err(SpannedError::new(
+            format!("<{}> must contain exactly one type tag", node.name),
+            node.span,
+        )
+        .with_code(ErrorCode::InvalidType));
+    }
+
+    let t = node.get_tag_at(0).ok_or(SpannedError::new(
+        "[INTERNAL ERROR] Cannot get inner type tag",
+        node.span,
+    ))?;
+
+    Ok(Spanned::new(parse_uvtype(t)?, node.span))
+}
+
 fn parse_union(node: &UVParseNode) -> Result<UVType, SpannedError> {
     if !node.all_tags() {
         return Err(SpannedError::new(
             "All children inside union tag must be known types",
             node.span,
-        ));
+        )
+        .with_code(ErrorCode::InvalidType));
     }
 
     if node.children_len() == 0 {
-        return Err(SpannedError::new("Union type cannot be empty", node.span));
+        return Err(SpannedError::new("Union type cannot be empty", node.span)
+            .with_code(ErrorCode::InvalidType));
     }
 
     if node.children_len() == 1 {
@@ -63,13 +90,13 @@ fn parse_union(node: &UVParseNode) -> Result<UVType, SpannedError> {
             node.span,
         ))?;
 
-        return Ok(parse(t)?);
+        return parse_uvtype(t);
     }
 
     let types = node
         .get_all_tags()
         .into_iter()
-        .map(parse)
+        .map(parse_uvtype)
         .collect::<Result<Vec<UVType>, SpannedError>>()?;
 
     Ok(UVType::new_union(types))
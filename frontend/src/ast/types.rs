@@ -1,13 +1,16 @@
+use std::{fmt, rc::Rc};
+
 use crate::{
+    analyzer::scope::SymbolTable,
     ast::traits::{
-        ArgumentsCount, GetType, IsAssignable, IsVariadic, StringToUVCompareOp,
+        ArgumentsCount, GetType, GetTypeScope, IsAssignable, IsVariadic, StringToUVCompareOp,
         StringToUVLogicalOp, StringToUVMathOp, StringToUVType,
     },
-    types::{Span, Spanned},
+    types::{Positional, Span, Spanned},
 };
 
 /// Typed value container
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UVValue {
     Int(i64),
     Float(f64),
@@ -28,6 +31,19 @@ impl GetType for UVValue {
     }
 }
 
+impl fmt::Display for UVValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UVValue::Int(value) => write!(f, "{value}"),
+            // Debug formatting keeps fractional part for whole floats (`2.0` instead of `2`)
+            UVValue::Float(value) => write!(f, "{value:?}"),
+            UVValue::String(value) => write!(f, "{value}"),
+            UVValue::Boolean(value) => write!(f, "{value}"),
+            UVValue::Null => write!(f, "null"),
+        }
+    }
+}
+
 /// Ultraviolet primitive types
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum UVType {
@@ -85,6 +101,22 @@ impl IsAssignable for UVType {
     }
 }
 
+impl fmt::Display for UVType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UVType::Int => write!(f, "int"),
+            UVType::Float => write!(f, "float"),
+            UVType::String => write!(f, "str"),
+            UVType::Boolean => write!(f, "bool"),
+            UVType::Null => write!(f, "null"),
+            UVType::Union(types) => {
+                let names: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", names.join(" | "))
+            }
+        }
+    }
+}
+
 // -------------------- String-Type conversion --------------
 
 impl StringToUVType for str {
@@ -100,29 +132,14 @@ impl StringToUVType for str {
     }
 }
 
-// ---------------
-/*
-#[derive(Debug)]
-pub enum Symbol {
-    /// Primitive type
-    Primitive(UVValue),
-
-    /// Name of the variable in scope
-    Variable(String),
-}
+// --------------------------- AST-TYPES ---------------------------
 
-impl GetTypeScope for Symbol {
-    fn get_type_from_scope(&self, scope: Option<usize>) -> UVType {
-        match self {
-            Self::Primitive(val) => val.get_type(),
-            // Scope-based search of the final primitive
-            Self::Variable(var) => todo!(),
-        }
-    }
-}
-*/
+/// Unique identity of the AST node, that names are bound by.
+/// Spans cannot be used for it, because spans of different sources (e.g. REPL entries) overlap.
+/// Ids are assigned by `AstGenerator`, so they are unique only among nodes of one generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(pub(super) usize);
 
-// --------------------------- AST-TYPES ---------------------------
 #[derive(Debug)]
 pub enum ASTBlockType {
     Program(Box<ProgramBlock>),
@@ -131,25 +148,54 @@ pub enum ASTBlockType {
     MainBlock(Vec<ASTBlockType>),
 
     VariableDefinition(VariableDefinition),
-    FunctionDefinition(),
+    /// Shared, so the interpreter can keep defined functions without cloning nodes
+    FunctionDefinition(Rc<FunctionDefinition>),
 
-    FunctionCall(),
+    FunctionCall(FunctionCall),
     VariableAssignment(VariableAssign),
     VariableAccess(VariableAccess),
 
-    ConditionalOp(),
+    ConditionalOp(ConditionalOp),
+    Return(Return),
 
     MathOp(MathOp),
     LogicalOp(LogicalOp),
     CompareOp(CompareOp),
 
-    ForLoop(),
-    WhileLoop(),
+    ForLoop(ForLoop),
+    WhileLoop(WhileLoop),
 
     Value(Spanned<UVValue>),
-    Type(UVType),
+    Type(Spanned<UVType>),
+
+    Print(Print),
+
+    GroupBlock(GroupBlock),
+}
 
-    GroupBlock(),
+impl Positional for ASTBlockType {
+    fn get_span(&self) -> Span {
+        match self {
+            ASTBlockType::Program(program) => program.span,
+            ASTBlockType::HeadBlock(_) | ASTBlockType::MainBlock(_) => Span::default(),
+            ASTBlockType::VariableDefinition(def) => def.span,
+            ASTBlockType::FunctionDefinition(function) => function.span,
+            ASTBlockType::FunctionCall(call) => call.span,
+            ASTBlockType::VariableAssignment(assign) => assign.span,
+            ASTBlockType::VariableAccess(access) => access.span,
+            ASTBlockType::ConditionalOp(op) => op.span,
+            ASTBlockType::Return(ret) => ret.span,
+            ASTBlockType::MathOp(op) => op.span,
+            ASTBlockType::LogicalOp(op) => op.span,
+            ASTBlockType::CompareOp(op) => op.span,
+            ASTBlockType::ForLoop(for_loop) => for_loop.span,
+            ASTBlockType::WhileLoop(while_loop) => while_loop.span,
+            ASTBlockType::Value(value) => value.span,
+            ASTBlockType::Type(t) => t.span,
+            ASTBlockType::GroupBlock(group) => group.span,
+            ASTBlockType::Print(print) => print.span,
+        }
+    }
 }
 
 // --------------------------- PROGRAM BLOCK ------------------------
@@ -167,15 +213,27 @@ pub struct ProgramBlock {
 #[derive(Debug)]
 pub struct VariableDefinition {
     pub name: Spanned<String>,
+
+    /// Declared type `<type>`. Type is inferred from the value when omitted
+    pub var_type: Option<Spanned<UVType>>,
+
     pub value: Spanned<Box<ASTBlockType>>,
-    pub is_const: bool,
+    /// Span of the `<const />` tag, if variable is a constant
+    pub const_span: Option<Span>,
 
     pub span: Span,
+    pub id: NodeId,
 }
 
-impl GetType for VariableDefinition {
-    fn get_type(&self) -> UVType {
-        todo!()
+impl VariableDefinition {
+    pub fn is_const(&self) -> bool {
+        self.const_span.is_some()
+    }
+}
+
+impl GetTypeScope for VariableDefinition {
+    fn get_type_from_scope(&self, symbols: &SymbolTable) -> Option<UVType> {
+        symbols.resolve(self.id)?.symbol_type.clone()
     }
 }
 
@@ -187,6 +245,13 @@ pub struct VariableAssign {
     pub value: Spanned<Box<ASTBlockType>>,
 
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl GetTypeScope for VariableAssign {
+    fn get_type_from_scope(&self, symbols: &SymbolTable) -> Option<UVType> {
+        symbols.resolve(self.id)?.symbol_type.clone()
+    }
 }
 
 // ------------------------ Variable Access ----------------------------------
@@ -195,6 +260,13 @@ pub struct VariableAssign {
 pub struct VariableAccess {
     pub name: String,
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl GetTypeScope for VariableAccess {
+    fn get_type_from_scope(&self, symbols: &SymbolTable) -> Option<UVType> {
+        symbols.resolve(self.id)?.symbol_type.clone()
+    }
 }
 
 // ------------------------ Math Operations ----------------------------------
@@ -205,7 +277,7 @@ pub struct MathOp {
     pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MathOpType {
     Sum,
     Sub,
@@ -227,6 +299,18 @@ impl StringToUVMathOp for str {
     }
 }
 
+impl fmt::Display for MathOpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MathOpType::Sum => "sum",
+            MathOpType::Sub => "sub",
+            MathOpType::Mul => "mul",
+            MathOpType::Div => "div",
+            MathOpType::Mod => "mod",
+        })
+    }
+}
+
 impl IsVariadic for MathOpType {
     fn is_variadic(&self) -> bool {
         match self {
@@ -238,7 +322,7 @@ impl IsVariadic for MathOpType {
 
 // ----------------------- Compare Operators ---------------------------------
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CompareOpType {
     Equality,
     NotEquality,
@@ -255,10 +339,23 @@ pub struct CompareOp {
     pub span: Span,
 }
 
+impl fmt::Display for CompareOpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CompareOpType::Equality => "eq",
+            CompareOpType::NotEquality => "neq",
+            CompareOpType::Greater => "gt",
+            CompareOpType::GreaterEquals => "gte",
+            CompareOpType::Less => "lt",
+            CompareOpType::LessEquals => "lte",
+        })
+    }
+}
+
 impl IsVariadic for CompareOpType {
     fn is_variadic(&self) -> bool {
         // TODO: In interpreter, implement proper handling variadic arguments
-        return false;
+        false
     }
 }
 
@@ -277,7 +374,7 @@ impl StringToUVCompareOp for str {
 }
 
 // ----------------------- Logical Operators ---------------------------------
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LogicalOpType {
     And,
     Or,
@@ -291,6 +388,16 @@ pub struct LogicalOp {
     pub span: Span,
 }
 
+impl fmt::Display for LogicalOpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogicalOpType::And => "and",
+            LogicalOpType::Or => "or",
+            LogicalOpType::Not => "not",
+        })
+    }
+}
+
 impl ArgumentsCount for LogicalOpType {
     fn min_arguments_count(&self) -> usize {
         match self {
@@ -318,6 +425,129 @@ impl StringToUVLogicalOp for str {
     }
 }
 
+// ------------------------- Function Definition -----------------------------
+
+#[derive(Debug)]
+pub struct FunctionDefinition {
+    pub name: Spanned<String>,
+    pub params: Vec<FunctionParam>,
+
+    /// Declared return type. Function returns `null` when omitted
+    pub return_type: Option<Spanned<UVType>>,
+
+    pub body: Spanned<Vec<ASTBlockType>>,
+
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug)]
+pub struct FunctionParam {
+    pub name: Spanned<String>,
+    pub param_type: Spanned<UVType>,
+
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug)]
+pub struct Return {
+    pub value: Option<Spanned<Box<ASTBlockType>>>,
+    pub span: Span,
+}
+
+// ---------------------------- Function Call --------------------------------
+
+#[derive(Debug)]
+pub struct FunctionCall {
+    /// Name of the called function
+    pub callee: Spanned<String>,
+
+    /// Positional arguments followed by named ones
+    pub args: Vec<CallArgument>,
+
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug)]
+pub struct CallArgument {
+    /// Parameter name for named arguments `<arg name>`
+    pub name: Option<Spanned<String>>,
+    pub value: Spanned<Box<ASTBlockType>>,
+
+    pub span: Span,
+}
+
+// ----------------------- Conditional Operator ------------------------------
+
+#[derive(Debug)]
+pub struct ConditionalOp {
+    /// `if` branch followed by all `elif` branches
+    pub branches: Vec<ConditionalBranch>,
+    pub else_body: Option<Spanned<Vec<ASTBlockType>>>,
+
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct ConditionalBranch {
+    pub condition: Spanned<Box<ASTBlockType>>,
+    pub body: Spanned<Vec<ASTBlockType>>,
+
+    pub span: Span,
+}
+
+// ---------------------------- For Loop -------------------------------------
+
+#[derive(Debug)]
+pub struct ForLoop {
+    /// Name of the loop variable
+    pub iterator: Spanned<String>,
+
+    pub start: Spanned<Box<ASTBlockType>>,
+
+    /// Exclusive upper bound
+    pub end: Spanned<Box<ASTBlockType>>,
+
+    /// Iterator increment. Defaults to 1 when omitted
+    pub step: Option<Spanned<Box<ASTBlockType>>>,
+
+    pub body: Spanned<Vec<ASTBlockType>>,
+
+    pub span: Span,
+    pub id: NodeId,
+}
+
+// ---------------------------- While Loop -----------------------------------
+
+#[derive(Debug)]
+pub struct WhileLoop {
+    pub condition: Spanned<Box<ASTBlockType>>,
+    pub body: Spanned<Vec<ASTBlockType>>,
+
+    pub span: Span,
+}
+
+// ---------------------------- Group Block ----------------------------------
+
+/// Sequence of statements with its own scope.
+/// Value of the block is the value of its last expression
+#[derive(Debug)]
+pub struct GroupBlock {
+    pub statements: Vec<ASTBlockType>,
+    pub span: Span,
+}
+
+// ------------------------------- Print -------------------------------------
+
+/// Built-in <println>. Prints operands separated by space
+#[derive(Debug)]
+pub struct Print {
+    pub operands: Vec<ASTBlockType>,
+    pub span: Span,
+}
+
 // ---------------------------- TESTS ----------------------------------------
 
 #[cfg(test)]
@@ -340,22 +570,15 @@ mod tests {
 
     #[test]
     fn type_compatible_with() {
-        assert_eq!(
-            UVType::Union(vec![UVType::Int, UVType::Null]).is_assignable_from(&UVType::Null),
-            true
-        );
+        assert!(UVType::Union(vec![UVType::Int, UVType::Null]).is_assignable_from(&UVType::Null));
 
-        assert_eq!(
+        assert!(
             UVType::Union(vec![UVType::Int, UVType::Float])
-                .is_assignable_from(&UVType::Union(vec![UVType::Int])),
-            true
+                .is_assignable_from(&UVType::Union(vec![UVType::Int]))
         );
 
-        assert_eq!(
-            UVType::Int.is_assignable_from(&UVType::Union(vec![UVType::Int, UVType::Null])),
-            false
-        );
+        assert!(!UVType::Int.is_assignable_from(&UVType::Union(vec![UVType::Int, UVType::Null])));
 
-        assert_eq!(UVType::Int.is_assignable_from(&UVType::Boolean), false);
+        assert!(!UVType::Int.is_assignable_from(&UVType::Boolean));
     }
 }
@@ -1,31 +1,37 @@
-use crate::types::Spanned;
+use crate::types::{Positional, Span, Spanned};
 use crate::{
+    Diagnostics,
     ast::{
-        GeneratorOutputType,
+        GeneratorOutputType, check_extra_attributes,
+        escapes::unescape,
         types::{ASTBlockType, UVValue},
     },
-    errors::SpannedError,
-    tokens_parser::types::UVParseNode,
+    errors::{SpannedError, codes::ErrorCode},
+    tokens_parser::types::{UVParseBody, UVParseNode},
 };
 
 /// Parse UVValues.
 /// Caller must guarantee, that tag name is one of data types!
 pub fn parse_value(node: &UVParseNode) -> GeneratorOutputType {
+    check_extra_attributes(node, &["value"])?;
+
     Ok(ASTBlockType::Value(Spanned::new(
         match node.name.as_str() {
             "int" => UVValue::Int(parse_int(node)?),
             "float" => UVValue::Float(parse_float(node)?),
-            "str" => UVValue::String(parse_str(node)),
+            "str" => UVValue::String(parse_str(node)?),
+            "estr" => UVValue::String(parse_escaped_str(node)?),
             "bool" => UVValue::Boolean(parse_boolean(node)?),
             "null" => {
-                validate_null(&node)?;
+                validate_null(node)?;
                 UVValue::Null
             }
             _ => {
                 return Err(SpannedError::new(
                     format!("Unknown value type `{}`", node.name),
                     node.span,
-                ));
+                )
+                .with_code(ErrorCode::InvalidLiteral));
             }
         },
         node.span,
@@ -38,48 +44,260 @@ fn validate_inner(node: &UVParseNode) -> Result<(), SpannedError> {
         return Err(SpannedError::new(
             format!("Invalid value for `{}` type", node.name),
             node.span,
-        ));
+        )
+        .with_code(ErrorCode::InvalidLiteral));
     }
     Ok(())
 }
 
+/// Get literal of the value, that is written as the only child or in `value` attribute
+fn get_value_literal(node: &UVParseNode) -> Result<&Spanned<String>, SpannedError> {
+    let Some(attr) = node.get_attribute("value") else {
+        validate_inner(node)?;
+        return Ok(node.get_inner_literal().unwrap()); // This unwrap is safe due checks above
+    };
+
+    if let Some(content) = node.get_child_at(0) {
+        return Err(SpannedError::new(
+            "Value is specified both in `value` attribute and tag content",
+            attr.span,
+        )
+        .with_code(ErrorCode::InvalidAttribute)
+        .with_label(content.get_span(), "tag content here"));
+    }
+
+    attr.value.as_ref().ok_or(
+        SpannedError::new("`value` attribute must have a value", attr.span)
+            .with_code(ErrorCode::InvalidAttribute)
+            .with_help("write the value in quotes: value=\"...\""),
+    )
+}
+
 fn parse_int(node: &UVParseNode) -> Result<i64, SpannedError> {
-    validate_inner(&node)?;
-    let inner_contents = node.get_inner_literal().unwrap(); // This unwrap is safe due checks above
+    parse_int_literal(get_value_literal(node)?)
+}
+
+/**
+Parse integer literal
+
+Literal can have a sign, `0x`, `0o` or `0b` prefix and `_` separators between digits.
+Like in Rust, separator can be placed right after the prefix (`0x_FF`)
+*/
+fn parse_int_literal(literal: &Spanned<String>) -> Result<i64, SpannedError> {
+    let (negative, unsigned) = split_sign(&literal.value);
+    let (radix, digits) = match unsigned.get(..2).map(|p| p.to_ascii_lowercase()).as_deref() {
+        Some("0x") => (16, &unsigned[2..]),
+        Some("0o") => (8, &unsigned[2..]),
+        Some("0b") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+
+    // Literal never contains whitespaces, so chars are mapped to the span one to one
+    let digits_start = literal.span.start + literal.value.chars().count() - digits.chars().count();
+    let mut cleaned = String::new();
+    for (i, ch) in digits.chars().enumerate() {
+        let span = Span::new(digits_start + i, digits_start + i + 1);
+        match ch {
+            '_' => validate_separator(digits, i, radix, radix != 10, span)?,
+            ch if ch.is_digit(radix) => cleaned.push(ch),
+            ch => {
+                return Err(SpannedError::new(
+                    format!(
+                        "Invalid digit `{ch}` in integer literal `{}`",
+                        literal.value
+                    ),
+                    span,
+                )
+                .with_code(ErrorCode::InvalidLiteral)
+                .with_primary_label(format!("not a base {radix} digit")));
+            }
+        }
+    }
+
+    if cleaned.is_empty() {
+        return Err(SpannedError::new(
+            format!("Integer literal `{}` has no digits", literal.value),
+            literal.span,
+        )
+        .with_code(ErrorCode::InvalidLiteral));
+    }
+
+    // Digits are validated, so parsing can fail only due to overflow
+    let value = u64::from_str_radix(&cleaned, radix)
+        .ok()
+        .and_then(|magnitude| match negative {
+            true => 0i64.checked_sub_unsigned(magnitude),
+            false => i64::try_from(magnitude).ok(),
+        });
 
-    inner_contents.value.parse::<i64>().map_err(|_| {
+    value.ok_or_else(|| {
         SpannedError::new(
-            format!("Cannot parse `{}` to an integer", inner_contents.value),
-            inner_contents.span,
+            format!(
+                "Integer literal `{}` is out of range for `int`",
+                literal.value
+            ),
+            literal.span,
         )
+        .with_code(ErrorCode::InvalidLiteral)
+        .with_primary_label("does not fit into 64-bit signed integer")
+        .with_note(format!(
+            "`int` values are in range from {} to {}",
+            i64::MIN,
+            i64::MAX
+        ))
     })
 }
 
 fn parse_float(node: &UVParseNode) -> Result<f64, SpannedError> {
-    validate_inner(&node)?;
-    let inner_contents = node.get_inner_literal().unwrap(); // This unwrap is safe due checks above
+    parse_float_literal(get_value_literal(node)?)
+}
+
+/**
+Parse float literal
 
-    inner_contents.value.parse::<f64>().map_err(|_| {
+Literal can have a sign, an exponent and `_` separators between digits.
+`inf` and `NaN` are accepted as well
+*/
+fn parse_float_literal(literal: &Spanned<String>) -> Result<f64, SpannedError> {
+    let mut cleaned = String::new();
+    for (i, ch) in literal.value.chars().enumerate() {
+        let span = Span::new(literal.span.start + i, literal.span.start + i + 1);
+        match ch {
+            '_' => validate_separator(&literal.value, i, 10, false, span)?,
+            ch => cleaned.push(ch),
+        }
+    }
+
+    let value = cleaned.parse::<f64>().map_err(|_| {
         SpannedError::new(
-            format!("Cannot parse `{}` to a float", inner_contents.value),
-            inner_contents.span,
+            format!("Cannot parse `{}` to a float", literal.value),
+            literal.span,
         )
-    })
+        .with_code(ErrorCode::InvalidLiteral)
+    })?;
+
+    let (_, unsigned) = split_sign(&cleaned);
+    let is_infinity = ["inf", "infinity"]
+        .iter()
+        .any(|inf| unsigned.eq_ignore_ascii_case(inf));
+    if value.is_infinite() && !is_infinity {
+        return Err(SpannedError::new(
+            format!(
+                "Float literal `{}` is out of range for `float`",
+                literal.value
+            ),
+            literal.span,
+        )
+        .with_code(ErrorCode::InvalidLiteral)
+        .with_primary_label("does not fit into 64-bit float")
+        .with_note(format!(
+            "`float` values are up to {:e} by magnitude",
+            f64::MAX
+        )));
+    }
+
+    Ok(value)
 }
 
-fn parse_str(node: &UVParseNode) -> String {
-    let inner_contents = if let Some(lit) = node.get_inner_literal() {
-        lit.value.clone()
-    } else {
-        String::new()
-    };
+/// Split `+` or `-` sign of the number. Returns true, if number is negative
+fn split_sign(number: &str) -> (bool, &str) {
+    match number.as_bytes().first() {
+        Some(b'-') => (true, &number[1..]),
+        Some(b'+') => (false, &number[1..]),
+        _ => (false, number),
+    }
+}
+
+/**
+Guarantee, that `_` separator at char index `pos` is placed between digits.
+
+Separator can also be the first char, if `number` follows the radix prefix.
+Repeated separators are rejected
+*/
+fn validate_separator(
+    number: &str,
+    pos: usize,
+    radix: u32,
+    after_prefix: bool,
+    span: Span,
+) -> Result<(), SpannedError> {
+    let is_digit = |ch: Option<char>| ch.is_some_and(|ch| ch.is_digit(radix));
+    let before = pos.checked_sub(1).and_then(|pos| number.chars().nth(pos));
+    let after = number.chars().nth(pos + 1);
+
+    if after == Some('_') {
+        return Err(
+            SpannedError::new("Digit separator `_` cannot be repeated", span)
+                .with_code(ErrorCode::InvalidLiteral),
+        );
+    }
+
+    let leading = pos == 0 && after_prefix;
+    if (is_digit(before) || leading) && is_digit(after) {
+        return Ok(());
+    }
+
+    Err(
+        SpannedError::new("Digit separator `_` must be placed between digits", span)
+            .with_code(ErrorCode::InvalidLiteral),
+    )
+}
+
+/// Parse string. Lines of `<istr>` are separate literals, so they are joined back
+fn parse_str(node: &UVParseNode) -> Result<String, SpannedError> {
+    if node.has_attribute("value") {
+        return Ok(get_value_literal(node)?.value.clone());
+    }
+
+    Ok(node
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            UVParseBody::String(literal) => Some(literal.value.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<&str>>()
+        .join("\n"))
+}
+
+/// Get literal of the `<estr>`. Empty tag has no literal
+fn get_escaped_literal(node: &UVParseNode) -> Result<Option<&Spanned<String>>, SpannedError> {
+    match node.has_attribute("value") {
+        true => get_value_literal(node).map(Some),
+        false => Ok(node.get_inner_literal()),
+    }
+}
+
+/**
+Parse `<estr>` content with decoding of escapes
 
-    inner_contents
+Only the first invalid escape is returned here,
+all of them are reported by `check_escapes` before generating statements
+*/
+fn parse_escaped_str(node: &UVParseNode) -> Result<String, SpannedError> {
+    match get_escaped_literal(node)? {
+        Some(literal) => unescape(literal).map_err(|mut errors| errors.swap_remove(0)),
+        None => Ok(String::new()),
+    }
+}
+
+/// Collect invalid escapes of all `<estr>` tags inside the node
+pub fn check_escapes(node: &UVParseNode, errors: &mut Diagnostics) {
+    if node.name == "estr"
+        && !node.self_closing
+        && let Ok(Some(literal)) = get_escaped_literal(node)
+        && let Err(escape_errors) = unescape(literal)
+    {
+        errors.extend(escape_errors);
+    }
+
+    for child in node.get_all_tags() {
+        check_escapes(child, errors);
+    }
 }
 
 fn parse_boolean(node: &UVParseNode) -> Result<bool, SpannedError> {
-    validate_inner(&node)?;
-    let inner_contents = node.get_inner_literal().unwrap(); // This unwrap is safe due checks above
+    let inner_contents = get_value_literal(node)?;
 
     match inner_contents.value.as_str() {
         "1" | "true" => Ok(true),
@@ -87,17 +305,177 @@ fn parse_boolean(node: &UVParseNode) -> Result<bool, SpannedError> {
         _ => Err(SpannedError::new(
             format!("Cannot parse `{}` to a boolean", inner_contents.value),
             inner_contents.span,
-        )),
+        )
+        .with_code(ErrorCode::InvalidLiteral)),
     }
 }
 
 fn validate_null(node: &UVParseNode) -> Result<(), SpannedError> {
     if !node.self_closing {
-        return Err(SpannedError::new(
-            "`null` tag must be self-closing",
-            node.span,
-        ));
+        return Err(
+            SpannedError::new("`null` tag must be self-closing", node.span)
+                .with_code(ErrorCode::InvalidLiteral),
+        );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{
+            AstGenerator, generate_ast,
+            types::{ASTBlockType, UVValue},
+        },
+        errors::SpannedError,
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+        types::{Positional, Span},
+    };
+
+    fn get_value(code: &str) -> Result<UVValue, SpannedError> {
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        match generate_ast(&mut AstGenerator::default(), &node)? {
+            ASTBlockType::Value(value) => Ok(value.value),
+            _ => panic!("Expected value"),
+        }
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(get_value("<int>-5</int>").unwrap(), UVValue::Int(-5));
+        assert_eq!(get_value("<int>+5</int>").unwrap(), UVValue::Int(5));
+        assert_eq!(get_value("<int>0xFF</int>").unwrap(), UVValue::Int(255));
+        assert_eq!(get_value("<int>-0o17</int>").unwrap(), UVValue::Int(-15));
+        assert_eq!(get_value("<int>0b1010</int>").unwrap(), UVValue::Int(10));
+        assert_eq!(
+            get_value("<int>1_000_000</int>").unwrap(),
+            UVValue::Int(1_000_000)
+        );
+        assert_eq!(get_value("<int>0x_FF</int>").unwrap(), UVValue::Int(255));
+        assert_eq!(
+            get_value(r#"<int value="-0x_FF"/>"#).unwrap(),
+            UVValue::Int(-255)
+        );
+        assert_eq!(
+            get_value("<int>-9223372036854775808</int>").unwrap(),
+            UVValue::Int(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn invalid_integers() {
+        let err = get_value("<int>9223372036854775808</int>").unwrap_err();
+        assert!(err.message().contains("out of range"));
+        assert_eq!(err.get_span(), Span::new(5, 24));
+
+        let err = get_value("<int>0b102</int>").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Invalid digit `2` in integer literal `0b102`"
+        );
+        assert_eq!(err.get_span(), Span::new(9, 10));
+
+        let err = get_value("<int>1__2</int>").unwrap_err();
+        assert_eq!(err.message(), "Digit separator `_` cannot be repeated");
+        assert_eq!(err.get_span(), Span::new(6, 7));
+
+        let err = get_value("<int>1_</int>").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Digit separator `_` must be placed between digits"
+        );
+        assert_eq!(err.get_span(), Span::new(6, 7));
+
+        assert!(get_value("<int>_1</int>").is_err());
+        assert!(get_value("<int>0x</int>").is_err());
+        assert!(get_value("<int>0x_</int>").is_err());
+        assert!(get_value("<int>0x__1</int>").is_err());
+        assert!(get_value("<int>1.5</int>").is_err());
+    }
+
+    #[test]
+    fn escaped_strings() {
+        assert_eq!(
+            get_value(r"<estr>a\tb&lt;</estr>").unwrap(),
+            UVValue::String("a\tb<".to_owned())
+        );
+        assert_eq!(
+            get_value("<estr></estr>").unwrap(),
+            UVValue::String(String::new())
+        );
+        assert_eq!(
+            get_value(r"<estr>\x</estr>").unwrap_err().get_span(),
+            Span::new(6, 8)
+        );
+    }
+
+    #[test]
+    fn indented_strings() {
+        assert_eq!(
+            get_value("<istr>\n    <a>\n      b\n    </istr>").unwrap(),
+            UVValue::String("<a>\n  b".to_owned())
+        );
+        assert_eq!(
+            get_value("<istr-x>\n</istr-x>").unwrap(),
+            UVValue::String(String::new())
+        );
+    }
+
+    #[test]
+    fn value_attribute() {
+        assert_eq!(get_value(r#"<int value="-5"/>"#).unwrap(), UVValue::Int(-5));
+        assert_eq!(
+            get_value(r#"<str value="a b"></str>"#).unwrap(),
+            UVValue::String("a b".to_owned())
+        );
+        assert_eq!(
+            get_value(r#"<bool value="true" />"#).unwrap(),
+            UVValue::Boolean(true)
+        );
+
+        // Errors point inside of the quotes
+        let err = get_value(r#"<int value="1x"/>"#).unwrap_err();
+        assert_eq!(err.get_span(), Span::new(13, 14));
+
+        let err = get_value(r#"<int value="1">2</int>"#).unwrap_err();
+        assert_eq!(err.get_span(), Span::new(5, 14));
+        assert!(get_value(r#"<int value />"#).is_err());
+        assert!(get_value(r#"<int val="1"/>"#).is_err());
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            get_value("<float>1e-3</float>").unwrap(),
+            UVValue::Float(1e-3)
+        );
+        assert_eq!(
+            get_value("<float>-2.5E+2</float>").unwrap(),
+            UVValue::Float(-250.0)
+        );
+        assert_eq!(
+            get_value("<float>1_000.5</float>").unwrap(),
+            UVValue::Float(1000.5)
+        );
+        assert_eq!(
+            get_value("<float>-inf</float>").unwrap(),
+            UVValue::Float(f64::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn invalid_floats() {
+        let err = get_value("<float>1e400</float>").unwrap_err();
+        assert!(err.message().contains("out of range"));
+        assert_eq!(err.get_span(), Span::new(7, 12));
+
+        assert!(get_value("<float>1._5</float>").is_err());
+        assert!(get_value("<float>1__0.5</float>").is_err());
+        assert!(get_value("<float>_1.5</float>").is_err());
+        assert!(get_value("<float>1e</float>").is_err());
+    }
+}
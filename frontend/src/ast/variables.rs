@@ -1,102 +1,102 @@
-use colored::Colorize;
-
 use crate::{
     ast::{
-        GeneratorOutputType, generate_ast, is_valid_identifier,
+        AstGenerator, GeneratorOutputType, check_extra_attributes, check_extra_children,
+        generate_ast, parse_identifier, parse_identifier_attribute, parse_single_expression,
+        type_parser::parse_type_block,
         types::{ASTBlockType, VariableAccess, VariableAssign, VariableDefinition},
     },
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     tokens_parser::types::UVParseNode,
     types::{Positional, Spanned},
 };
 
 /// Parse definition of variables <let>
-pub fn parse_var_definition(node: &UVParseNode) -> GeneratorOutputType {
-    let extra = node.search_extra_children(vec!["name", "value", "const"]);
-    if !extra.is_empty() {
-        let first = extra.first().ok_or(SpannedError::new(
-            "[INTERNAL ERROR] Cannot get first extra child",
-            node.span,
-        ))?;
+pub fn parse_var_definition(
+    generator: &mut AstGenerator,
+    node: &UVParseNode,
+) -> GeneratorOutputType {
+    check_extra_children(
+        node,
+        vec!["name", "type", "value", "const"],
+        "Found extra children for variable definition",
+    )?;
+    check_extra_attributes(node, &["name", "const"])?;
 
-        return Err(SpannedError::new(
-            "Found extra children for variable definition",
-            first.get_span(),
-        ));
-    }
-
-    let name_block = node.get_child_by_name("name").ok_or(SpannedError::new(
-        "Variable definition should have an inner <name> tag",
-        node.span,
-    ))?;
-
-    if name_block.children_len() != 1 || !name_block.all_literals() {
-        return Err(SpannedError::new("Invalid variable name", name_block.span));
-    }
-
-    let name = name_block.get_inner_literal().ok_or(SpannedError::new(
-        "[INTERNAL ERROR] Cannot get inner literal",
-        node.span,
-    ))?;
-
-    if !is_valid_identifier(&name.value) {
-        return Err(SpannedError::new(
-            format!("`{}` is not a valid name for variable", name.value),
-            name.span,
-        ));
-    }
-
-    let value_block = node
-        .get_child_by_name("value")
-        .ok_or(SpannedError::new("Variable must be initialized", node.span))?;
+    let name = match (node.get_child_by_name("name"), node.get_attribute("name")) {
+        (Some(name_block), None) => parse_identifier(name_block, "variable")?,
+        (None, Some(name_attr)) => parse_identifier_attribute(name_attr, "variable")?,
+        (Some(name_block), Some(name_attr)) => {
+            return Err(SpannedError::new(
+                "Variable name is specified both in `name` attribute and <name> tag",
+                name_attr.span,
+            )
+            .with_code(ErrorCode::InvalidAttribute)
+            .with_label(name_block.span, "<name> tag here"));
+        }
+        (None, None) => {
+            return Err(SpannedError::new(
+                "Variable definition should have an inner <name> tag or `name` attribute",
+                node.span,
+            )
+            .with_code(ErrorCode::InvalidTagContent));
+        }
+    };
 
-    if value_block.children_len() != 1 || !value_block.all_tags() {
-        return Err(SpannedError::new(
-            format!(
-                "Variable value must have only one inner tag.\n{}{}",
-                "tip".green(),
-                ": If you want to place multiple tags, wrap them in a <b> tag.",
-            ),
-            value_block.span,
-        ));
-    }
+    let value_block = node.get_child_by_name("value").ok_or(
+        SpannedError::new("Variable must be initialized", node.span)
+            .with_code(ErrorCode::InvalidTagContent),
+    )?;
 
-    let value = value_block.get_tag_at(0).ok_or(SpannedError::new(
-        "[INTERNAL ERROR] Cannot get inner tag",
-        node.span,
-    ))?;
+    let var_type = match node.get_child_by_name("type") {
+        Some(type_block) => Some(parse_type_block(type_block)?),
+        None => None,
+    };
 
-    let is_const = match node.get_child_by_name("const") {
-        Some(c) if !c.self_closing => {
+    let const_span = match (node.get_child_by_name("const"), node.get_attribute("const")) {
+        (Some(c), _) if !c.self_closing => {
+            return Err(
+                SpannedError::new("`const` tag must be self-closing", c.span)
+                    .with_code(ErrorCode::InvalidTagContent),
+            );
+        }
+        (_, Some(c)) if c.value.is_some() => {
             return Err(SpannedError::new(
-                "`const` tag must be self-closing",
+                "`const` attribute is a flag and cannot have a value",
                 c.span,
-            ));
+            )
+            .with_code(ErrorCode::InvalidAttribute));
         }
-        Some(_) => true,
-        None => false,
+        (Some(c), _) => Some(c.span),
+        (None, Some(c)) => Some(c.span),
+        (None, None) => None,
     };
 
+    // Name is bound after the value, which cannot refer to the variable itself
+    let value = parse_single_expression(generator, value_block, "Variable value")?;
+    generator.bind(&name.value);
+
     Ok(ASTBlockType::VariableDefinition(VariableDefinition {
-        name: Spanned::new(name.value.clone(), name_block.span),
-        value: Spanned::new(Box::new(generate_ast(value)?), value_block.span),
-        is_const: is_const,
+        name,
+        var_type,
+        value,
+        const_span,
         span: node.span,
+        id: generator.next_id(),
     }))
 }
 
 /// Parse variable assignment
-pub fn parse_var_assign(node: &UVParseNode) -> GeneratorOutputType {
+pub fn parse_var_assign(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
     if !node.all_tags() {
         let unexpected_lit = node.get_inner_literal().ok_or(SpannedError::new(
             "[INTERNAL ERROR] Cannot get inner literal for error",
             node.span,
         ))?;
 
-        return Err(SpannedError::new(
-            "Cannot assign literal to a variable",
-            unexpected_lit.span,
-        ));
+        return Err(
+            SpannedError::new("Cannot assign literal to a variable", unexpected_lit.span)
+                .with_code(ErrorCode::InvalidTagContent),
+        );
     }
 
     if node.children_len() != 1 {
@@ -108,7 +108,8 @@ pub fn parse_var_assign(node: &UVParseNode) -> GeneratorOutputType {
         return Err(SpannedError::new(
             "Variable assign should have only one nested tag",
             extra.get_span(),
-        ));
+        )
+        .with_code(ErrorCode::InvalidTagContent));
     }
 
     let value = node
@@ -117,22 +118,185 @@ pub fn parse_var_assign(node: &UVParseNode) -> GeneratorOutputType {
 
     Ok(ASTBlockType::VariableAssignment(VariableAssign {
         name: node.name.clone(),
-        value: Spanned::new(Box::new(generate_ast(value)?), value.span),
+        value: Spanned::new(Box::new(generate_ast(generator, value)?), value.span),
         span: node.span,
+        id: generator.next_id(),
     }))
 }
 
 /// Parse variable access block
-pub fn parse_var_access(node: &UVParseNode) -> GeneratorOutputType {
+pub fn parse_var_access(generator: &mut AstGenerator, node: &UVParseNode) -> GeneratorOutputType {
     if !node.self_closing {
-        return Err(SpannedError::new(
-            "Variable access block should be self-closing",
-            node.span,
-        ));
+        return Err(
+            SpannedError::new("Variable access block should be self-closing", node.span)
+                .with_code(ErrorCode::InvalidTagContent),
+        );
     }
 
     Ok(ASTBlockType::VariableAccess(VariableAccess {
         name: node.name.clone(),
         span: node.span,
+        id: generator.next_id(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{
+            AstGenerator, GeneratorOutputType, generate_ast,
+            types::{ASTBlockType, UVType},
+        },
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+    };
+
+    fn get_ast(code: &str) -> GeneratorOutputType {
+        let node = TokenParser::new(Lexer::new(code.to_owned()).parse())
+            .parse()
+            .unwrap();
+        generate_ast(&mut AstGenerator::default(), &node)
+    }
+
+    #[test]
+    fn definition() {
+        let ASTBlockType::VariableDefinition(def) =
+            get_ast("<let><name>x</name><value><int>1</int></value><const /></let>").unwrap()
+        else {
+            panic!("Expected variable definition");
+        };
+
+        assert_eq!(def.name.value, "x");
+        assert!(def.is_const());
+    }
+
+    #[test]
+    fn definition_with_type() {
+        let ASTBlockType::VariableDefinition(def) = get_ast(
+            "<let><name>x</name><type><union><int /><null /></union></type>\
+            <value><null /></value></let>",
+        )
+        .unwrap() else {
+            panic!("Expected variable definition");
+        };
+
+        assert_eq!(
+            def.var_type.unwrap().value,
+            UVType::Union(vec![UVType::Int, UVType::Null])
+        );
+    }
+
+    #[test]
+    fn definition_with_attributes() {
+        let ASTBlockType::VariableDefinition(def) =
+            get_ast(r#"<let name="x" const><value><int value="1"/></value></let>"#).unwrap()
+        else {
+            panic!("Expected variable definition");
+        };
+
+        assert_eq!(def.name.value, "x");
+        assert!(def.is_const());
+
+        assert!(get_ast(r#"<let name="1x"><value><int>1</int></value></let>"#).is_err());
+        assert!(
+            get_ast(r#"<let name="x"><name>y</name><value><int>1</int></value></let>"#).is_err()
+        );
+        assert!(get_ast(r#"<let name="x" mut><value><int>1</int></value></let>"#).is_err());
+    }
+
+    #[test]
+    fn multiple_values() {
+        let error = get_ast("<let><name>x</name><value><int>1</int><int>2</int></value></let>")
+            .unwrap_err();
+        assert!(
+            error
+                .message()
+                .starts_with("Variable value must have only one inner tag.")
+        );
+    }
+
+    #[test]
+    fn group_block_value() {
+        let ASTBlockType::VariableDefinition(def) = get_ast(
+            "<let><name>x</name><value><b>\
+            <let><name>y</name><value><int>1</int></value></let>\
+            <sum><y /><int>2</int></sum>\
+            </b></value></let>",
+        )
+        .unwrap() else {
+            panic!("Expected variable definition");
+        };
+
+        let ASTBlockType::GroupBlock(group) = *def.value.value else {
+            panic!("Expected group block");
+        };
+
+        assert_eq!(group.statements.len(), 2);
+        assert!(matches!(group.statements[1], ASTBlockType::MathOp(_)));
+    }
+
+    #[test]
+    fn bound_group_name() {
+        let ASTBlockType::GroupBlock(group) = get_ast(
+            r#"<b><b><int>1</int></b><let name="b"><value><int>2</int></value></let>
+            <b><int>3</int></b><b /></b>"#,
+        )
+        .unwrap() else {
+            panic!("Expected group block");
+        };
+
+        assert!(matches!(group.statements[0], ASTBlockType::GroupBlock(_)));
+        assert!(matches!(
+            group.statements[2],
+            ASTBlockType::VariableAssignment(_)
+        ));
+        assert!(matches!(
+            group.statements[3],
+            ASTBlockType::VariableAccess(_)
+        ));
+
+        // Binding of the nested block is not visible after it
+        let ASTBlockType::GroupBlock(group) = get_ast(
+            r#"<b><b><let name="b"><value><int>1</int></value></let></b><b><int>2</int></b></b>"#,
+        )
+        .unwrap() else {
+            panic!("Expected group block");
+        };
+        assert!(matches!(group.statements[1], ASTBlockType::GroupBlock(_)));
+    }
+
+    #[test]
+    fn assignment() {
+        assert!(matches!(
+            get_ast("<x><int>1</int></x>").unwrap(),
+            ASTBlockType::VariableAssignment(_)
+        ));
+        assert!(get_ast("<x><int>1</int><int>2</int></x>").is_err());
+    }
+
+    #[test]
+    fn operator_names() {
+        assert!(get_ast("<let><name>sum</name><value><int>1</int></value></let>").is_ok());
+        assert!(matches!(
+            get_ast("<sum />").unwrap(),
+            ASTBlockType::VariableAccess(_)
+        ));
+        assert!(get_ast("<let><name>call</name><value><int>1</int></value></let>").is_err());
+    }
+
+    #[test]
+    fn node_ids() {
+        let node = TokenParser::new(Lexer::new("<x />".to_owned()).parse())
+            .parse()
+            .unwrap();
+        let id = |generator: &mut AstGenerator| match generate_ast(generator, &node).unwrap() {
+            ASTBlockType::VariableAccess(access) => access.id,
+            _ => panic!("Expected variable access"),
+        };
+
+        let mut generator = AstGenerator::default();
+        let first = id(&mut generator);
+        assert_ne!(id(&mut generator), first);
+        assert_eq!(id(&mut AstGenerator::default()), first);
+    }
+}
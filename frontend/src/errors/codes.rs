@@ -0,0 +1,320 @@
+use std::fmt;
+
+/// Stable diagnostic codes. Codes are never reused, even if an error is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    UnexpectedToken,
+    MismatchedClosingTag,
+    UnexpectedEof,
+    InvalidProgramStructure,
+    UnexpectedTag,
+    InvalidTagContent,
+    InvalidName,
+    InvalidLiteral,
+    InvalidType,
+    UnresolvedName,
+    Redefinition,
+    AssignToConstant,
+    ReturnOutsideFunction,
+    TypeMismatch,
+    InvalidArguments,
+    MissingReturn,
+    DivisionByZero,
+    IntegerOverflow,
+    CallDepthExceeded,
+    InvalidLoopBounds,
+    InvalidEscape,
+    InvalidAttribute,
+    UnknownInstruction,
+}
+
+/// Long description of the error code
+pub struct Explanation {
+    pub title: &'static str,
+    pub description: &'static str,
+
+    /// Code, that causes the error
+    pub erroneous: &'static str,
+
+    /// The same code with the error fixed
+    pub fixed: &'static str,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 23] = [
+        ErrorCode::UnexpectedToken,
+        ErrorCode::MismatchedClosingTag,
+        ErrorCode::UnexpectedEof,
+        ErrorCode::InvalidProgramStructure,
+        ErrorCode::UnexpectedTag,
+        ErrorCode::InvalidTagContent,
+        ErrorCode::InvalidName,
+        ErrorCode::InvalidLiteral,
+        ErrorCode::InvalidType,
+        ErrorCode::UnresolvedName,
+        ErrorCode::Redefinition,
+        ErrorCode::AssignToConstant,
+        ErrorCode::ReturnOutsideFunction,
+        ErrorCode::TypeMismatch,
+        ErrorCode::InvalidArguments,
+        ErrorCode::MissingReturn,
+        ErrorCode::DivisionByZero,
+        ErrorCode::IntegerOverflow,
+        ErrorCode::CallDepthExceeded,
+        ErrorCode::InvalidLoopBounds,
+        ErrorCode::InvalidEscape,
+        ErrorCode::InvalidAttribute,
+        ErrorCode::UnknownInstruction,
+    ];
+
+    /// Get code in the `UV0001` form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "UV0001",
+            ErrorCode::MismatchedClosingTag => "UV0002",
+            ErrorCode::UnexpectedEof => "UV0003",
+            ErrorCode::InvalidProgramStructure => "UV0004",
+            ErrorCode::UnexpectedTag => "UV0005",
+            ErrorCode::InvalidTagContent => "UV0006",
+            ErrorCode::InvalidName => "UV0007",
+            ErrorCode::InvalidLiteral => "UV0008",
+            ErrorCode::InvalidType => "UV0009",
+            ErrorCode::UnresolvedName => "UV0010",
+            ErrorCode::Redefinition => "UV0011",
+            ErrorCode::AssignToConstant => "UV0012",
+            ErrorCode::ReturnOutsideFunction => "UV0013",
+            ErrorCode::TypeMismatch => "UV0014",
+            ErrorCode::InvalidArguments => "UV0015",
+            ErrorCode::MissingReturn => "UV0016",
+            ErrorCode::DivisionByZero => "UV0017",
+            ErrorCode::IntegerOverflow => "UV0018",
+            ErrorCode::CallDepthExceeded => "UV0019",
+            ErrorCode::InvalidLoopBounds => "UV0020",
+            ErrorCode::InvalidEscape => "UV0021",
+            ErrorCode::InvalidAttribute => "UV0022",
+            ErrorCode::UnknownInstruction => "UV0023",
+        }
+    }
+
+    /// Find code by its `UV0001` form. Case of the prefix is ignored
+    pub fn lookup(code: &str) -> Option<ErrorCode> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Get entry of the explain catalog
+    pub fn explain(&self) -> Explanation {
+        match self {
+            ErrorCode::UnexpectedToken => Explanation {
+                title: "Unexpected token",
+                description: "The token is not allowed at this position of a tag. \
+                    Tag headers contain only a name and attributes, \
+                    and bodies contain only tags and literals.",
+                erroneous: "<println><int>1</int>?</println>",
+                fixed: "<println><int>1</int></println>",
+            },
+            ErrorCode::MismatchedClosingTag => Explanation {
+                title: "Mismatched closing tag",
+                description: "A closing tag must have the same name as the innermost open tag. \
+                    Usually a closing tag is misspelled or the inner tag was not closed.",
+                erroneous: "<println><int>1</println>",
+                fixed: "<println><int>1</int></println>",
+            },
+            ErrorCode::UnexpectedEof => Explanation {
+                title: "Unexpected end of file",
+                description: "The source ended before all tags were closed.",
+                erroneous: "<program><main><println><int>1</int></println>",
+                fixed: "<program><main><println><int>1</int></println></main></program>",
+            },
+            ErrorCode::InvalidProgramStructure => Explanation {
+                title: "Invalid program structure",
+                description: "A program must be a single <program> tag with a required <main> \
+                    block and an optional <head> block. Root blocks contain only tags, \
+                    and functions can be defined only on the root level. \
+                    Processing instructions such as `<?uv version=\"1.0\"?>` \
+                    can be placed only before the root tag.",
+                erroneous: "<main><println><int>1</int></println></main>",
+                fixed: "<program><main><println><int>1</int></println></main></program>",
+            },
+            ErrorCode::UnexpectedTag => Explanation {
+                title: "Unexpected tag",
+                description: "The tag is neither a keyword, an operator, a type nor a valid \
+                    variable name, or it is not allowed at this position.",
+                erroneous: "<let><name>x</name><value><int>1</int></value><mut /></let>",
+                fixed: "<let><name>x</name><value><int>1</int></value></let>",
+            },
+            ErrorCode::InvalidTagContent => Explanation {
+                title: "Invalid tag content",
+                description: "The tag has a wrong number or kind of children. For example, \
+                    wrappers such as <value> hold exactly one expression \
+                    and operators require a minimal count of operands.",
+                erroneous: "<let><name>x</name><value><int>1</int><int>2</int></value></let>",
+                fixed: "<let><name>x</name><value><sum><int>1</int><int>2</int></sum></value></let>",
+            },
+            ErrorCode::InvalidName => Explanation {
+                title: "Invalid name",
+                description: "Names of variables, functions and parameters must start with \
+                    a letter or `_`, contain only letters, digits and `_`, \
+                    and must not be a reserved tag. \
+                    Tag names and attribute keys cannot contain `-` or `+`.",
+                erroneous: "<let><name>1x</name><value><int>1</int></value></let>",
+                fixed: "<let><name>x1</name><value><int>1</int></value></let>",
+            },
+            ErrorCode::InvalidLiteral => Explanation {
+                title: "Invalid literal",
+                description: "The content of the value tag cannot be parsed to its type. \
+                    Integers can have a sign, `0x`, `0o` or `0b` prefix and single `_` separators \
+                    between digits or right after the prefix, and must fit into 64 bits. \
+                    Floats can also have an exponent.",
+                erroneous: "<int>one</int>",
+                fixed: "<int>1</int>",
+            },
+            ErrorCode::InvalidType => Explanation {
+                title: "Invalid type",
+                description: "Types are written as self-closing type tags. \
+                    <union> must contain at least one type tag.",
+                erroneous: "<type><int>1</int></type>",
+                fixed: "<type><int /></type>",
+            },
+            ErrorCode::UnresolvedName => Explanation {
+                title: "Unresolved name",
+                description: "The variable or function is not defined in any visible scope, \
+                    or the variable is used before its definition.",
+                erroneous: "<println><x /></println>",
+                fixed: "<let><name>x</name><value><int>1</int></value></let>\n\
+                    <println><x /></println>",
+            },
+            ErrorCode::Redefinition => Explanation {
+                title: "Name is already defined",
+                description: "A name can be defined only once in the same scope. \
+                    Inner scopes can shadow names of outer ones.",
+                erroneous: "<let><name>x</name><value><int>1</int></value></let>\n\
+                    <let><name>x</name><value><int>2</int></value></let>",
+                fixed: "<let><name>x</name><value><int>1</int></value></let>\n\
+                    <x><int>2</int></x>",
+            },
+            ErrorCode::AssignToConstant => Explanation {
+                title: "Assignment to constant",
+                description: "Variables declared with <const /> cannot be changed.",
+                erroneous: "<let><name>x</name><value><int>1</int></value><const /></let>\n\
+                    <x><int>2</int></x>",
+                fixed: "<let><name>x</name><value><int>1</int></value></let>\n\
+                    <x><int>2</int></x>",
+            },
+            ErrorCode::ReturnOutsideFunction => Explanation {
+                title: "Return outside of function",
+                description: "<return> can be used only inside a function body.",
+                erroneous: "<main><return><int>1</int></return></main>",
+                fixed: "<main><fn><name>f</name><returns><int /></returns>\
+                    <body><return><int>1</int></return></body></fn></main>",
+            },
+            ErrorCode::TypeMismatch => Explanation {
+                title: "Type mismatch",
+                description: "The value has a type, that is not allowed at this position.",
+                erroneous: "<let><name>x</name><type><int /></type>\
+                    <value><str>a</str></value></let>",
+                fixed: "<let><name>x</name><type><str /></type>\
+                    <value><str>a</str></value></let>",
+            },
+            ErrorCode::InvalidArguments => Explanation {
+                title: "Invalid arguments",
+                description: "Every parameter of the function must be passed exactly once, \
+                    and no other arguments can be passed.",
+                erroneous: "<call twice></call>",
+                fixed: "<call twice><arg><int>2</int></arg></call>",
+            },
+            ErrorCode::MissingReturn => Explanation {
+                title: "Missing return",
+                description: "Function with a return type must return a value on every path.",
+                erroneous: "<fn><name>f</name><returns><int /></returns><body></body></fn>",
+                fixed: "<fn><name>f</name><returns><int /></returns>\
+                    <body><return><int>1</int></return></body></fn>",
+            },
+            ErrorCode::DivisionByZero => Explanation {
+                title: "Division by zero",
+                description: "Integer division or remainder by zero was attempted at runtime.",
+                erroneous: "<div><int>1</int><int>0</int></div>",
+                fixed: "<div><int>1</int><int>1</int></div>",
+            },
+            ErrorCode::IntegerOverflow => Explanation {
+                title: "Integer overflow",
+                description: "Result of the integer operation does not fit into 64 bits.",
+                erroneous: "<mul><int>9223372036854775807</int><int>2</int></mul>",
+                fixed: "<mul><float>9223372036854775807</float><float>2</float></mul>",
+            },
+            ErrorCode::CallDepthExceeded => Explanation {
+                title: "Maximum call depth exceeded",
+                description: "Too many nested function calls. \
+                    Usually a recursive function has no base case.",
+                erroneous: "<fn><name>f</name><body><call f></call></body></fn>",
+                fixed: "<fn><name>f</name><body></body></fn>",
+            },
+            ErrorCode::InvalidLoopBounds => Explanation {
+                title: "Invalid loop bounds",
+                description: "Bounds of the <for> loop must be numeric and the step \
+                    cannot be zero.",
+                erroneous: "<for><iterator>i</iterator><start><int>0</int></start>\
+                    <end><int>3</int></end><step><int>0</int></step><body></body></for>",
+                fixed: "<for><iterator>i</iterator><start><int>0</int></start>\
+                    <end><int>3</int></end><step><int>1</int></step><body></body></for>",
+            },
+            ErrorCode::InvalidEscape => Explanation {
+                title: "Invalid escape sequence",
+                description: "<estr> literals decode `\\n`, `\\t`, `\\r`, `\\0`, `\\\\` \
+                    and `\\u{...}` escapes, `&lt;`, `&gt;`, `&amp;`, `&quot;` and `&apos;` \
+                    entities and numeric character references such as `&#65;` or `&#x41;`. \
+                    Any other use of `\\` or `&` is an error.",
+                erroneous: "<estr>Tom & Jerry\\q</estr>",
+                fixed: "<estr>Tom &amp; Jerry\\\\q</estr>",
+            },
+            ErrorCode::InvalidAttribute => Explanation {
+                title: "Invalid attribute",
+                description: "Attributes are written in the tag header as `key=\"value\"` \
+                    or as bare flags. Every key can be used only once, values must be quoted, \
+                    and the tag must support the attribute. A value cannot be specified \
+                    both in an attribute and in the tag content.",
+                erroneous: "<int value=5></int>",
+                fixed: "<int value=\"5\" />",
+            },
+            ErrorCode::UnknownInstruction => Explanation {
+                title: "Unknown processing instruction",
+                description: "Only the `uv` processing instruction is understood, \
+                    and only with a supported language version. Other instructions, \
+                    such as the XML declaration, are ignored. \
+                    This is a warning, it does not stop the program.",
+                erroneous: "<?uv version=\"99\"?><program><main></main></program>",
+                fixed: "<?uv version=\"1.0\"?><program><main></main></program>",
+            },
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::errors::codes::ErrorCode;
+
+    #[test]
+    fn codes_are_unique() {
+        let codes: HashSet<&str> = ErrorCode::ALL.iter().map(|c| c.as_str()).collect();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(
+            ErrorCode::lookup("UV0002"),
+            Some(ErrorCode::MismatchedClosingTag)
+        );
+        assert_eq!(ErrorCode::lookup("uv0002"), ErrorCode::lookup("UV0002"));
+        assert_eq!(ErrorCode::lookup("UV9999"), None);
+    }
+}
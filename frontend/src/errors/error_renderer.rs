@@ -1,10 +1,13 @@
 use crate::{
-    errors::SpannedError,
+    errors::{
+        SpannedError,
+        snippet::{Annotation, line_no_width, render_snippet},
+    },
     types::{Positional, SourceFile},
 };
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::Colorize;
-use std::{cmp::min, fmt::Write};
+use std::fmt::Write;
 
 /// Trait for positional errors, that renders error messages
 pub trait ErrorRenderer {
@@ -14,6 +17,9 @@ pub trait ErrorRenderer {
     /// Display simple error with message
     fn display_with_source(&self, source: &SourceFile) -> String;
 
+    /// Render severity and code header, e.g. `error[UV0001]`
+    fn render_header(&self) -> String;
+
     /// Render extended error message
     fn render_extended(&self, source: &SourceFile) -> Result<String>;
 }
@@ -30,41 +36,170 @@ impl ErrorRenderer for SpannedError {
 
         let (line, col) = source.get_line_col(self.get_span());
         format!(
-            "\n{}: {}",
+            "\n{}: {}: {}",
             self.render_error_line(line, col, source).red(),
+            self.render_header(),
             self.message
         )
     }
 
+    fn render_header(&self) -> String {
+        let header = match self.code() {
+            Some(code) => format!("{}[{code}]", self.severity()),
+            None => self.severity().to_string(),
+        };
+        header.color(self.severity().color()).to_string()
+    }
+
     fn render_extended(&self, source: &SourceFile) -> Result<String> {
         let (line, col) = source.get_line_col(self.get_span());
-        let mut line_content = source.get_line_content(line)?;
-        let original_len = line_content.len();
-
-        line_content = line_content.trim_start();
-        let col_offsetted = col
-            .checked_sub(original_len - line_content.len())
-            .context("")?;
-
         let error_line_link = self.render_error_line(line, col, source);
 
-        let editor_line = line + 1;
-        let line_no_len = editor_line.to_string().len();
+        let annotations: Vec<Annotation> = std::iter::once(Annotation::new(
+            source,
+            self.span,
+            self.primary_label(),
+            true,
+        ))
+        .chain(
+            self.labels()
+                .iter()
+                .map(|l| Annotation::new(source, l.span, Some(&l.message), false)),
+        )
+        .collect();
 
         let mut output = String::new();
-        writeln!(output, "{}: {}", "error".red(), self.message.bold())?;
+        writeln!(output, "{}: {}", self.render_header(), self.message.bold())?;
         writeln!(output, " --> {}", error_line_link)?;
-        writeln!(output, " {} |", " ".repeat(line_no_len))?;
-        writeln!(output, " {} | {}", editor_line, line_content)?;
         writeln!(
             output,
-            " {} | {}{}",
-            " ".repeat(line_no_len),
-            " ".repeat(col_offsetted),
-            "^".repeat(min(self.span.end - self.span.start, line_content.len() - 1))
-                .red()
+            "{}",
+            render_snippet(source, &annotations, self.severity().color())?
         )?;
 
+        let blank = " ".repeat(line_no_width(&annotations));
+        if !self.footer().is_empty() {
+            writeln!(output, " {blank} |")?;
+        }
+        for (severity, message) in self.footer() {
+            writeln!(
+                output,
+                " {blank} = {}: {message}",
+                severity.to_string().bold()
+            )?;
+        }
+
         Ok(output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        errors::{SpannedError, error_renderer::ErrorRenderer},
+        types::{SourceFile, Span},
+    };
+
+    fn render(code: &str, error: SpannedError) -> String {
+        error
+            .render_extended(&SourceFile::from_string(code))
+            .unwrap()
+    }
+
+    #[test]
+    fn secondary_labels() {
+        let error = SpannedError::new(
+            "Unexpected closing tag `c`. Expected `b`",
+            Span::new(20, 21),
+        )
+        .with_primary_label("closing tag here")
+        .with_label(Span::new(1, 2), "opening tag here")
+        .with_help("rename the closing tag");
+
+        assert_eq!(
+            render("<b><int>1</int>\n  </c>", error),
+            "error: Unexpected closing tag `c`. Expected `b`\n\
+            \x20--> <string>:2:4\n\
+            \x20  |\n\
+            \x201 | <b><int>1</int>\n\
+            \x20  |  - opening tag here\n\
+            \x202 |   </c>\n\
+            \x20  |     ^ closing tag here\n\
+            \x20  |\n\
+            \x20  = help: rename the closing tag\n"
+        );
+    }
+
+    #[test]
+    fn labels_on_the_same_line() {
+        let error = SpannedError::new("Type mismatch", Span::new(10, 15))
+            .with_primary_label("found `str`")
+            .with_label(Span::new(0, 3), "declared here")
+            .with_label(Span::new(5, 7), "and here");
+
+        assert_eq!(
+            render("let x int = \"a\"", error),
+            "error: Type mismatch\n\
+            \x20--> <string>:1:10\n\
+            \x20  |\n\
+            \x201 | let x int = \"a\"\n\
+            \x20  | ---  --   ^^^^^ found `str`\n\
+            \x20  | |    |\n\
+            \x20  | |    and here\n\
+            \x20  | declared here\n"
+        );
+    }
+
+    #[test]
+    fn multiline_span() {
+        let code = "<main>\n    <b>\n        <int>1</int>\n    </b>\n</main>";
+        let error = SpannedError::new("Invalid block", Span::new(11, 41))
+            .with_primary_label("block")
+            .with_label(Span::new(1, 5), "inside of main");
+
+        assert_eq!(
+            render(code, error),
+            "error: Invalid block\n\
+            \x20--> <string>:2:4\n\
+            \x20  |\n\
+            \x201 |   <main>\n\
+            \x20  |    ---- inside of main\n\
+            \x202 |       <b>\n\
+            \x20  |  _____^\n\
+            \x203 | |         <int>1</int>\n\
+            \x204 | |     </b>\n\
+            \x20  | |_____^ block\n"
+        );
+    }
+
+    #[test]
+    fn long_multiline_span() {
+        let code = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk";
+        let error = SpannedError::new("Long", Span::new(2, 19));
+
+        assert_eq!(
+            render(code, error),
+            "error: Long\n\
+            \x20--> <string>:2:0\n\
+            \x20   |\n\
+            \x20 2 |   b\n\
+            \x20   |  _^\n\
+            \x20 3 | | c\n\
+            ...\n\
+            \x20 9 | | i\n\
+            \x2010 | | j\n\
+            \x20   | |_^\n"
+        );
+    }
+
+    #[test]
+    fn empty_span() {
+        assert!(
+            render(
+                "<a>\n",
+                SpannedError::new("Unexpected EOF", Span::new(4, 4))
+            )
+            .ends_with(" 2 |\n   | ^\n")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use serde_json::{Value, json};
+
+use crate::{
+    errors::SpannedError,
+    types::{Positional, SourceFile, Span},
+};
+
+/**
+Convert span to JSON location
+
+Offsets are exclusive at the end. `line` and `column` are zero-based,
+as returned by [`SourceFile::get_line_col`]
+*/
+pub fn location_to_json(span: Span, source: &SourceFile) -> Value {
+    let (line, column) = source.get_line_col(span);
+    let (end_line, end_column) = source.get_line_col(Span::new(span.end, span.end));
+
+    json!({
+        "byte_start": source.byte_offset(span.start),
+        "byte_end": source.byte_offset(span.end),
+        "char_start": span.start,
+        "char_end": span.end,
+        "line": line,
+        "column": column,
+        "end_line": end_line,
+        "end_column": end_column,
+    })
+}
+
+/// Convert diagnostic to a JSON record
+pub fn diagnostic_to_json(diagnostic: &SpannedError, source: &SourceFile) -> Value {
+    json!({
+        "file": source.path.to_string_lossy(),
+        "severity": diagnostic.severity().to_string(),
+        "code": diagnostic.code().map(|code| code.as_str()),
+        "message": diagnostic.message(),
+        "span": location_to_json(diagnostic.get_span(), source),
+        "label": diagnostic.primary_label(),
+        "labels": diagnostic
+            .labels()
+            .iter()
+            .map(|label| json!({
+                "message": label.message,
+                "span": location_to_json(label.span, source),
+            }))
+            .collect::<Vec<Value>>(),
+        "notes": diagnostic
+            .footer()
+            .iter()
+            .map(|(severity, message)| json!({
+                "severity": severity.to_string(),
+                "message": message,
+            }))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        errors::{SpannedError, codes::ErrorCode, json::diagnostic_to_json},
+        types::{SourceFile, Span},
+    };
+
+    #[test]
+    fn record() {
+        let source = SourceFile::from_string("<b>\n«</c>");
+        let diagnostic =
+            SpannedError::new("Unexpected closing tag `c`. Expected `b`", Span::new(7, 8))
+                .with_code(ErrorCode::MismatchedClosingTag)
+                .with_primary_label("closing tag here")
+                .with_label(Span::new(1, 2), "opening tag here")
+                .with_help("rename the closing tag");
+
+        assert_eq!(
+            diagnostic_to_json(&diagnostic, &source),
+            json!({
+                "file": "<string>",
+                "severity": "error",
+                "code": "UV0002",
+                "message": "Unexpected closing tag `c`. Expected `b`",
+                "span": {
+                    "byte_start": 8,
+                    "byte_end": 9,
+                    "char_start": 7,
+                    "char_end": 8,
+                    "line": 1,
+                    "column": 3,
+                    "end_line": 1,
+                    "end_column": 4,
+                },
+                "label": "closing tag here",
+                "labels": [{
+                    "message": "opening tag here",
+                    "span": {
+                        "byte_start": 1,
+                        "byte_end": 2,
+                        "char_start": 1,
+                        "char_end": 2,
+                        "line": 0,
+                        "column": 1,
+                        "end_line": 0,
+                        "end_column": 2,
+                    },
+                }],
+                "notes": [{"severity": "help", "message": "rename the closing tag"}],
+            })
+        );
+    }
+}
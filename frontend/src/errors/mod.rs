@@ -1,13 +1,81 @@
 use std::fmt;
 
-use crate::types::{Positional, Span};
+use colored::Color;
 
+use crate::{
+    errors::codes::ErrorCode,
+    types::{Positional, Span},
+};
+
+pub mod codes;
 pub mod error_renderer;
+pub mod json;
+pub mod sarif;
+mod snippet;
+
+/// Importance of the diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    /// Get color of the severity for terminal output
+    pub fn color(&self) -> Color {
+        match self {
+            Severity::Error => Color::Red,
+            Severity::Warning => Color::Yellow,
+            Severity::Note => Color::Cyan,
+            Severity::Help => Color::Green,
+        }
+    }
+}
 
-/// Simple parse error
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+            Severity::Help => write!(f, "help"),
+        }
+    }
+}
+
+/// Secondary span of the diagnostic with explaining message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/**
+Positional diagnostic. It is an error with no code by default
+
+Diagnostic points to the primary span and can have labeled secondary spans
+and trailing `note:` and `help:` lines
+*/
 pub struct SpannedError {
     message: String,
     span: Span,
+    severity: Severity,
+    code: Option<ErrorCode>,
+
+    /// Labels and trailing lines are boxed, so errors stay small on the happy path
+    details: Option<Box<Details>>,
+}
+
+#[derive(Debug, Default)]
+struct Details {
+    /// Label of the primary span
+    primary_label: Option<String>,
+    labels: Vec<Label>,
+
+    /// Trailing lines, such as notes and help
+    footer: Vec<(Severity, String)>,
 }
 
 impl SpannedError {
@@ -16,8 +84,93 @@ impl SpannedError {
         Self {
             message: message.into(),
             span,
+            severity: Severity::Error,
+            code: None,
+            details: None,
         }
     }
+
+    fn details_mut(&mut self) -> &mut Details {
+        self.details.get_or_insert_with(Box::default)
+    }
+
+    /// Set label of the primary span
+    pub fn with_primary_label(mut self, message: impl Into<String>) -> Self {
+        self.details_mut().primary_label = Some(message.into());
+        self
+    }
+
+    /// Add labeled secondary span
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.details_mut().labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Add trailing `note:` line
+    pub fn with_note(mut self, message: impl Into<String>) -> Self {
+        self.details_mut()
+            .footer
+            .push((Severity::Note, message.into()));
+        self
+    }
+
+    /// Add trailing `help:` line
+    pub fn with_help(mut self, message: impl Into<String>) -> Self {
+        self.details_mut()
+            .footer
+            .push((Severity::Help, message.into()));
+        self
+    }
+
+    /// Set stable code, that can be looked up in the explain catalog
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Set severity of the diagnostic
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Get error message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get error code
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    /// Get diagnostic severity
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Get label of the primary span
+    pub fn primary_label(&self) -> Option<&str> {
+        self.details.as_ref()?.primary_label.as_deref()
+    }
+
+    /// Get labeled secondary spans
+    pub fn labels(&self) -> &[Label] {
+        self.details.as_ref().map_or(&[], |d| &d.labels)
+    }
+
+    /// Get trailing notes and help lines
+    pub fn footer(&self) -> &[(Severity, String)] {
+        self.details.as_ref().map_or(&[], |d| &d.footer)
+    }
+
+    /// Check if the diagnostic fails compilation
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
 }
 
 impl Positional for SpannedError {
@@ -31,6 +184,9 @@ impl fmt::Debug for SpannedError {
         f.debug_struct("SpannedError")
             .field("message", &self.message)
             .field("span", &self.span)
+            .field("severity", &self.severity)
+            .field("code", &self.code)
+            .field("details", &self.details)
             .finish()
     }
 }
@@ -40,6 +196,9 @@ impl fmt::Display for SpannedError {
         f.debug_struct("SpannedError")
             .field("message", &self.message)
             .field("span", &self.span)
+            .field("severity", &self.severity)
+            .field("code", &self.code)
+            .field("details", &self.details)
             .finish()
     }
 }
@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::{
+    errors::{Severity, SpannedError, codes::ErrorCode},
+    types::{Positional, SourceFile, Span},
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Get SARIF level of the severity. SARIF has no separate level for help
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note | Severity::Help => "note",
+    }
+}
+
+/**
+Convert path of the source to URI of the artifact
+
+Absolute paths become `file://` URIs, other paths become relative references
+(virtual sources, such as `<string>`, as well). Windows separators are replaced with `/`
+and all chars except unreserved ones are percent-encoded
+*/
+fn artifact_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let (mut uri, rest) = match path.as_bytes() {
+        [b'/', ..] => ("file://".to_owned(), path.as_str()),
+        [drive, b':', b'/', ..] if drive.is_ascii_alphabetic() => {
+            (format!("file:///{}:", *drive as char), &path[2..])
+        }
+        _ => (String::new(), path.as_str()),
+    };
+
+    for byte in rest.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+/**
+Convert span to SARIF physical location. Lines and columns are one-based
+
+Spans are counted in chars, so the run sets `columnKind` to `unicodeCodePoints`
+*/
+fn physical_location(span: Span, source: &SourceFile) -> Value {
+    let (line, column) = source.get_line_col(span);
+    let (end_line, end_column) = source.get_line_col(Span::new(span.end, span.end));
+
+    json!({
+        "artifactLocation": { "uri": artifact_uri(source.path) },
+        "region": {
+            "startLine": line + 1,
+            "startColumn": column + 1,
+            "endLine": end_line + 1,
+            "endColumn": end_column + 1,
+            "charOffset": span.start,
+            "charLength": span.end.saturating_sub(span.start),
+        },
+    })
+}
+
+/// Convert diagnostic to a SARIF result. Notes and help lines are appended to the message
+pub fn diagnostic_to_sarif(diagnostic: &SpannedError, source: &SourceFile) -> Value {
+    let mut text = diagnostic.message().to_owned();
+    for (severity, message) in diagnostic.footer() {
+        text.push_str(&format!("\n{severity}: {message}"));
+    }
+
+    let mut result = json!({
+        "level": level(diagnostic.severity()),
+        "message": { "text": text },
+        "locations": [{ "physicalLocation": physical_location(diagnostic.get_span(), source) }],
+    });
+
+    if let Some(code) = diagnostic.code() {
+        result["ruleId"] = json!(code.as_str());
+    }
+
+    if !diagnostic.labels().is_empty() {
+        result["relatedLocations"] = diagnostic
+            .labels()
+            .iter()
+            .enumerate()
+            .map(|(id, label)| {
+                json!({
+                    "id": id,
+                    "message": { "text": label.message },
+                    "physicalLocation": physical_location(label.span, source),
+                })
+            })
+            .collect();
+    }
+
+    result
+}
+
+/// Describe error code as a SARIF reporting rule
+fn rule(code: ErrorCode) -> Value {
+    let explanation = code.explain();
+
+    json!({
+        "id": code.as_str(),
+        "name": format!("{code:?}"),
+        "shortDescription": { "text": explanation.title },
+        "fullDescription": { "text": explanation.description },
+        "help": {
+            "text": format!(
+                "{}\n\nErroneous code example:\n{}\n\nFixed example:\n{}",
+                explanation.description, explanation.erroneous, explanation.fixed
+            ),
+        },
+    })
+}
+
+/// Wrap SARIF results to a complete SARIF 2.1.0 log with a single run
+pub fn sarif_log(results: Vec<Value>) -> Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "Ultraviolet",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": ErrorCode::ALL.into_iter().map(rule).collect::<Vec<Value>>(),
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::{
+        errors::{
+            SpannedError,
+            codes::ErrorCode,
+            sarif::{artifact_uri, diagnostic_to_sarif, sarif_log},
+        },
+        types::{SourceFile, Span},
+    };
+
+    #[test]
+    fn result() {
+        let source = SourceFile::from_string("<let><const /></let>\n<x />");
+        let diagnostic = SpannedError::new("Cannot assign to constant `x`", Span::new(21, 26))
+            .with_code(ErrorCode::AssignToConstant)
+            .with_label(Span::new(5, 14), "`x` is declared as constant here")
+            .with_help("remove `<const />` to make `x` mutable");
+
+        assert_eq!(
+            diagnostic_to_sarif(&diagnostic, &source),
+            json!({
+                "ruleId": "UV0012",
+                "level": "error",
+                "message": {
+                    "text": "Cannot assign to constant `x`\nhelp: remove `<const />` to make `x` mutable"
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "%3Cstring%3E" },
+                        "region": {
+                            "startLine": 2,
+                            "startColumn": 1,
+                            "endLine": 2,
+                            "endColumn": 6,
+                            "charOffset": 21,
+                            "charLength": 5,
+                        },
+                    },
+                }],
+                "relatedLocations": [{
+                    "id": 0,
+                    "message": { "text": "`x` is declared as constant here" },
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "%3Cstring%3E" },
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 6,
+                            "endLine": 1,
+                            "endColumn": 15,
+                            "charOffset": 5,
+                            "charLength": 9,
+                        },
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn log() {
+        let log = sarif_log(Vec::new());
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["columnKind"], "unicodeCodePoints");
+
+        let rules = log["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rules.len(), ErrorCode::ALL.len());
+        assert_eq!(rules[1]["id"], "UV0002");
+        assert_eq!(rules[1]["name"], "MismatchedClosingTag");
+        assert!(log["runs"][0]["results"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn uri() {
+        for (path, uri) in [
+            ("examples/main.uv", "examples/main.uv"),
+            ("my dir/a#b.uv", "my%20dir/a%23b.uv"),
+            ("<repl:1>", "%3Crepl%3A1%3E"),
+            ("ü.uv", "%C3%BC.uv"),
+            ("/tmp/x y.uv", "file:///tmp/x%20y.uv"),
+            (r"C:\src\main.uv", "file:///C:/src/main.uv"),
+        ] {
+            assert_eq!(artifact_uri(Path::new(path)), uri);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use colored::{Color, Colorize};
+
+use crate::types::{SourceFile, Span};
+
+/// Multiline spans longer than this are shown with elided middle lines
+const MAX_MULTILINE_LINES: usize = 4;
+
+/// Span of the diagnostic, resolved to lines and columns
+pub struct Annotation<'a> {
+    /// Line and column of the first char
+    start: (usize, usize),
+
+    /// Line and column of the last char
+    end: (usize, usize),
+
+    label: Option<&'a str>,
+    primary: bool,
+}
+
+impl<'a> Annotation<'a> {
+    pub fn new(source: &SourceFile, span: Span, label: Option<&'a str>, primary: bool) -> Self {
+        // Empty spans are rendered as a single marker
+        let last = span.end.max(span.start + 1) - 1;
+
+        Self {
+            start: source.get_line_col(span),
+            end: source.get_line_col(Span::new(last, last)),
+            label,
+            primary,
+        }
+    }
+
+    fn is_multiline(&self) -> bool {
+        self.start.0 != self.end.0
+    }
+
+    fn marker(&self) -> char {
+        if self.primary { '^' } else { '-' }
+    }
+}
+
+/// Row of the rendered snippet, where every cell can be colored
+#[derive(Default)]
+struct Row {
+    cells: Vec<(char, Option<Color>)>,
+}
+
+impl Row {
+    fn put(&mut self, col: usize, ch: char, color: Option<Color>) {
+        if self.cells.len() <= col {
+            self.cells.resize(col + 1, (' ', None));
+        }
+        self.cells[col] = (ch, color);
+    }
+
+    fn put_str(&mut self, col: usize, s: &str, color: Option<Color>) {
+        for (i, ch) in s.chars().enumerate() {
+            self.put(col + i, ch, color);
+        }
+    }
+
+    /// Render row, joining cells of the same color
+    fn render(&self) -> String {
+        let mut output = String::new();
+        let mut run = String::new();
+        let mut run_color = None;
+
+        for &(ch, color) in &self.cells {
+            if color != run_color {
+                output.push_str(&paint(&run, run_color));
+                run.clear();
+                run_color = color;
+            }
+            run.push(ch);
+        }
+        output.push_str(&paint(&run, run_color));
+        output.trim_end().to_owned()
+    }
+}
+
+fn paint(s: &str, color: Option<Color>) -> String {
+    match color {
+        Some(color) => s.color(color).to_string(),
+        None => s.to_owned(),
+    }
+}
+
+/**
+Render source lines with annotations, rustc-style
+
+Primary spans are underlined with `^` in the severity color, secondary with `-`.
+Multiline spans are connected with a line in the left gutter
+*/
+pub fn render_snippet(
+    source: &SourceFile,
+    annotations: &[Annotation],
+    color: Color,
+) -> Result<String> {
+    let color_of = |annotation: &Annotation| match annotation.primary {
+        true => Some(color),
+        false => Some(Color::Blue),
+    };
+
+    let mut lines = BTreeSet::new();
+    for annotation in annotations {
+        let (start, end) = (annotation.start.0, annotation.end.0);
+        if end - start > MAX_MULTILINE_LINES {
+            lines.extend([start, start + 1, end - 1, end]);
+        } else {
+            lines.extend(start..=end);
+        }
+    }
+
+    let contents = lines
+        .iter()
+        .map(|&line| Ok((line, source.get_line_content(line)?)))
+        .collect::<Result<Vec<(usize, &str)>>>()?;
+
+    // Common indentation is not displayed
+    let indent = contents
+        .iter()
+        .filter(|(_, content)| !content.trim().is_empty())
+        .map(|(_, content)| content.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+    let gutter = match annotations.iter().any(Annotation::is_multiline) {
+        true => 2,
+        false => 0,
+    };
+    let col = |col: usize| gutter + col.saturating_sub(indent);
+
+    let width = line_no_width(annotations);
+    let blank = " ".repeat(width);
+
+    let mut output = vec![format_row(&blank, &Row::default())];
+    let mut previous: Option<usize> = None;
+    for (line, content) in contents {
+        if previous.is_some_and(|previous| line > previous + 1) {
+            output.push("...".to_owned());
+        }
+        previous = Some(line);
+
+        // Multiline span, that continues through the source line or annotation rows
+        let through_source = annotations
+            .iter()
+            .find(|a| a.is_multiline() && a.start.0 < line && line <= a.end.0);
+        let through_rows = annotations
+            .iter()
+            .find(|a| a.is_multiline() && a.start.0 <= line && line <= a.end.0);
+        let with_gutter = |row: &mut Row| {
+            if let Some(multiline) = through_rows {
+                row.put(0, '|', color_of(multiline));
+            }
+        };
+
+        let mut row = Row::default();
+        if let Some(multiline) = through_source {
+            row.put(0, '|', color_of(multiline));
+        }
+        let content: String = content.chars().skip(indent).collect();
+        row.put_str(gutter, &content, None);
+        output.push(format_row(&format!("{:>width$}", line + 1), &row));
+
+        // Starts of multiline spans
+        for annotation in annotations
+            .iter()
+            .filter(|a| a.is_multiline() && a.start.0 == line)
+        {
+            let mut row = Row::default();
+            let start = col(annotation.start.1);
+            for i in 1..start {
+                row.put(i, '_', color_of(annotation));
+            }
+            row.put(start, annotation.marker(), color_of(annotation));
+            output.push(format_row(&blank, &row));
+        }
+
+        // Single line spans
+        let mut single: Vec<&Annotation> = annotations
+            .iter()
+            .filter(|a| !a.is_multiline() && a.start.0 == line)
+            .collect();
+        single.sort_by_key(|a| a.start.1);
+
+        if let Some((last, rest)) = single.split_last() {
+            // Secondary markers are drawn first, so primary ones are always visible
+            let mut markers = Row::default();
+            with_gutter(&mut markers);
+            for annotation in single.iter().filter(|a| !a.primary) {
+                for c in annotation.start.1..=annotation.end.1 {
+                    markers.put(col(c), annotation.marker(), color_of(annotation));
+                }
+            }
+            for annotation in single.iter().filter(|a| a.primary) {
+                for c in annotation.start.1..=annotation.end.1 {
+                    markers.put(col(c), annotation.marker(), color_of(annotation));
+                }
+            }
+
+            // Label of the rightmost span is placed on the same row
+            if let Some(label) = last.label {
+                let end = markers.cells.len();
+                markers.put_str(end + 1, label, color_of(last));
+            }
+            output.push(format_row(&blank, &markers));
+
+            // Other labels are placed below and connected with their spans
+            let labeled: Vec<&&Annotation> = rest.iter().filter(|a| a.label.is_some()).collect();
+            if !labeled.is_empty() {
+                let mut connectors = Row::default();
+                with_gutter(&mut connectors);
+                for annotation in &labeled {
+                    connectors.put(col(annotation.start.1), '|', color_of(annotation));
+                }
+                output.push(format_row(&blank, &connectors));
+            }
+            for (i, annotation) in labeled.iter().enumerate().rev() {
+                let mut row = Row::default();
+                with_gutter(&mut row);
+                for left in &labeled[..i] {
+                    row.put(col(left.start.1), '|', color_of(left));
+                }
+                let label = annotation.label.unwrap_or_default();
+                row.put_str(col(annotation.start.1), label, color_of(annotation));
+                output.push(format_row(&blank, &row));
+            }
+        }
+
+        // Ends of multiline spans
+        for annotation in annotations
+            .iter()
+            .filter(|a| a.is_multiline() && a.end.0 == line)
+        {
+            let mut row = Row::default();
+            let end = col(annotation.end.1);
+            row.put(0, '|', color_of(annotation));
+            for i in 1..end {
+                row.put(i, '_', color_of(annotation));
+            }
+            row.put(end, annotation.marker(), color_of(annotation));
+            if let Some(label) = annotation.label {
+                row.put_str(end + 2, label, color_of(annotation));
+            }
+            output.push(format_row(&blank, &row));
+        }
+    }
+
+    Ok(output.join("\n"))
+}
+
+/// Get width of the line numbers column
+pub fn line_no_width(annotations: &[Annotation]) -> usize {
+    let last_line = annotations.iter().map(|a| a.end.0).max().unwrap_or(0);
+    (last_line + 1).to_string().len()
+}
+
+/// Format row with the line number gutter
+fn format_row(line_no: &str, row: &Row) -> String {
+    match row.render() {
+        row if row.is_empty() => format!(" {line_no} |"),
+        row => format!(" {line_no} | {row}"),
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::ast::types::UVValue;
+
+/// Variables of a single function invocation
+#[derive(Debug)]
+struct Frame {
+    scopes: Vec<HashMap<String, UVValue>>,
+}
+
+impl Frame {
+    fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+}
+
+/// Runtime storage of variables.
+///
+/// Function calls get a separate frame, so callee cannot see variables of the caller
+#[derive(Debug)]
+pub struct Environment {
+    frames: Vec<Frame>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Environment {
+    /// Create environment with global frame
+    pub fn new() -> Self {
+        Self {
+            frames: vec![Frame::new()],
+        }
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().expect("Global frame is never popped")
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames
+            .last_mut()
+            .expect("Global frame is never popped")
+    }
+
+    /// Enter new block scope
+    pub fn push_scope(&mut self) {
+        self.frame_mut().scopes.push(HashMap::new());
+    }
+
+    /// Leave block scope
+    pub fn pop_scope(&mut self) {
+        self.frame_mut().scopes.pop();
+    }
+
+    /// Enter function call
+    pub fn push_frame(&mut self) {
+        self.frames.push(Frame::new());
+    }
+
+    /// Leave function call
+    pub fn pop_frame(&mut self) {
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+    }
+
+    /// Count of active function calls
+    pub fn call_depth(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    /// Define variable in the innermost scope
+    pub fn define(&mut self, name: &str, value: UVValue) {
+        if let Some(scope) = self.frame_mut().scopes.last_mut() {
+            scope.insert(name.to_owned(), value);
+        }
+    }
+
+    /// Get value of the nearest variable with provided name
+    pub fn get(&self, name: &str) -> Option<&UVValue> {
+        self.frame()
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+    }
+
+    /// Update value of the nearest variable. Returns `false` if variable is not defined
+    pub fn set(&mut self, name: &str, value: UVValue) -> bool {
+        match self
+            .frame_mut()
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,529 @@
+use std::{collections::HashMap, io::Write, rc::Rc};
+
+use crate::{
+    ast::{
+        traits::GetType,
+        types::{
+            ASTBlockType, CompareOpType, ForLoop, FunctionCall, FunctionDefinition, LogicalOp,
+            LogicalOpType, MathOp, MathOpType, UVType, UVValue,
+        },
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    interpreter::{
+        environment::Environment,
+        operations::{as_bool, binary_math, compare, math_identity, short_circuits},
+    },
+    types::{Positional, Span},
+};
+
+pub mod environment;
+pub mod operations;
+
+/// Maximum depth of nested function calls
+pub const MAX_CALL_DEPTH: usize = 256;
+
+/// Reason of the evaluation interruption
+enum Interrupt {
+    /// <return> statement unwinds to the nearest function call
+    Return(UVValue),
+
+    /// Error is boxed to keep stack frames of the deep recursion small
+    Error(Box<SpannedError>),
+}
+
+impl From<SpannedError> for Interrupt {
+    fn from(err: SpannedError) -> Self {
+        Interrupt::Error(Box::new(err))
+    }
+}
+
+type EvalResult = Result<UVValue, Interrupt>;
+
+/// Tree-walking interpreter over the AST.
+///
+/// Output of <println> is written to the provided writer
+pub struct Interpreter<W: Write> {
+    env: Environment,
+    functions: HashMap<String, Rc<FunctionDefinition>>,
+    output: W,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            env: Environment::new(),
+            functions: HashMap::new(),
+            output,
+        }
+    }
+
+    /// Get writer back
+    #[cfg(test)]
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Run <program> and get value of the last statement in <main>
+    pub fn run_program(&mut self, program: &ASTBlockType) -> Result<UVValue, SpannedError> {
+        let ASTBlockType::Program(program) = program else {
+            return Err(SpannedError::new(
+                "[INTERNAL ERROR] Interpreter expects <program> block",
+                program.get_span(),
+            ));
+        };
+
+        let mut root = Vec::new();
+        for block in program.head.iter().chain([&program.main]) {
+            if let ASTBlockType::HeadBlock(statements) | ASTBlockType::MainBlock(statements) = block
+            {
+                root.extend(statements);
+            }
+        }
+
+        self.run_statements(root)
+    }
+
+    /// Run top-level statements in the global scope.
+    /// Functions are declared before execution, so they can be called before definition
+    pub fn run_statements<'a>(
+        &mut self,
+        statements: impl IntoIterator<Item = &'a ASTBlockType>,
+    ) -> Result<UVValue, SpannedError> {
+        let statements: Vec<&ASTBlockType> = statements.into_iter().collect();
+        for statement in &statements {
+            if let ASTBlockType::FunctionDefinition(function) = statement {
+                self.declare_function(function);
+            }
+        }
+
+        let mut last = UVValue::Null;
+        for statement in statements {
+            last = match self.eval(statement) {
+                Ok(value) => value,
+                Err(Interrupt::Error(err)) => return Err(*err),
+                Err(Interrupt::Return(_)) => {
+                    return Err(SpannedError::new(
+                        "<return> cannot be used outside of function",
+                        statement.get_span(),
+                    )
+                    .with_code(ErrorCode::ReturnOutsideFunction));
+                }
+            };
+        }
+        Ok(last)
+    }
+
+    fn declare_function(&mut self, function: &Rc<FunctionDefinition>) {
+        self.functions
+            .insert(function.name.value.clone(), Rc::clone(function));
+    }
+
+    fn eval(&mut self, node: &ASTBlockType) -> EvalResult {
+        match node {
+            ASTBlockType::Program(_) | ASTBlockType::HeadBlock(_) | ASTBlockType::MainBlock(_) => {
+                Err(
+                    SpannedError::new("[INTERNAL ERROR] Unexpected root block", node.get_span())
+                        .into(),
+                )
+            }
+            ASTBlockType::VariableDefinition(def) => {
+                let value = self.eval(&def.value.value)?;
+                self.env.define(&def.name.value, value);
+                Ok(UVValue::Null)
+            }
+            ASTBlockType::FunctionDefinition(function) => {
+                self.declare_function(function);
+                Ok(UVValue::Null)
+            }
+            ASTBlockType::FunctionCall(call) => self.call(call),
+            ASTBlockType::VariableAssignment(assign) => {
+                let value = self.eval(&assign.value.value)?;
+                if !self.env.set(&assign.name, value) {
+                    return Err(undefined_variable(&assign.name, assign.span));
+                }
+                Ok(UVValue::Null)
+            }
+            ASTBlockType::VariableAccess(access) => self
+                .env
+                .get(&access.name)
+                .cloned()
+                .ok_or_else(|| undefined_variable(&access.name, access.span)),
+            ASTBlockType::ConditionalOp(op) => {
+                for branch in &op.branches {
+                    let condition = self.eval(&branch.condition.value)?;
+                    if as_bool(&condition, branch.condition.value.get_span())? {
+                        self.eval_block(&branch.body.value)?;
+                        return Ok(UVValue::Null);
+                    }
+                }
+
+                if let Some(else_body) = &op.else_body {
+                    self.eval_block(&else_body.value)?;
+                }
+                Ok(UVValue::Null)
+            }
+            ASTBlockType::Return(ret) => {
+                let value = match &ret.value {
+                    Some(value) => self.eval(&value.value)?,
+                    None => UVValue::Null,
+                };
+                Err(Interrupt::Return(value))
+            }
+            ASTBlockType::MathOp(op) => self.eval_math(op),
+            ASTBlockType::LogicalOp(op) => self.eval_logical(op),
+            ASTBlockType::CompareOp(op) => {
+                let left = self.eval(&op.operands[0])?;
+                let right = self.eval(&op.operands[1])?;
+                Ok(UVValue::Boolean(compare(
+                    &op.op_type,
+                    &left,
+                    &right,
+                    op.span,
+                )?))
+            }
+            ASTBlockType::ForLoop(for_loop) => self.eval_for_loop(for_loop),
+            ASTBlockType::WhileLoop(while_loop) => {
+                loop {
+                    let condition = self.eval(&while_loop.condition.value)?;
+                    if !as_bool(&condition, while_loop.condition.value.get_span())? {
+                        break;
+                    }
+                    self.eval_block(&while_loop.body.value)?;
+                }
+                Ok(UVValue::Null)
+            }
+            ASTBlockType::Value(value) => Ok(value.value.clone()),
+            ASTBlockType::Type(t) if t.value == UVType::Null => Ok(UVValue::Null),
+            ASTBlockType::Type(t) => Err(SpannedError::new(
+                format!("Type `{}` cannot be used as a value", t.value),
+                t.span,
+            )
+            .with_code(ErrorCode::TypeMismatch)
+            .into()),
+            ASTBlockType::GroupBlock(group) => self.eval_block(&group.statements),
+            ASTBlockType::Print(print) => {
+                let mut values = Vec::new();
+                for operand in &print.operands {
+                    values.push(self.eval(operand)?.to_string());
+                }
+
+                writeln!(self.output, "{}", values.join(" ")).map_err(|err| {
+                    SpannedError::new(format!("Cannot write output: {err}"), print.span)
+                })?;
+                Ok(UVValue::Null)
+            }
+        }
+    }
+
+    /// Evaluate statements in a new scope and get value of the last one
+    fn eval_block(&mut self, statements: &[ASTBlockType]) -> EvalResult {
+        self.env.push_scope();
+
+        let mut result = Ok(UVValue::Null);
+        for statement in statements {
+            result = self.eval(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.env.pop_scope();
+        result
+    }
+
+    fn eval_math(&mut self, op: &MathOp) -> EvalResult {
+        let mut operands = op.operands.iter();
+        let mut result = match operands.next() {
+            Some(first) => self.eval(first)?,
+            None => return Ok(math_identity(&op.op_type)),
+        };
+
+        for operand in operands {
+            let value = self.eval(operand)?;
+            result = binary_math(&op.op_type, &result, &value, op.span)?;
+        }
+        Ok(result)
+    }
+
+    fn eval_logical(&mut self, op: &LogicalOp) -> EvalResult {
+        if let LogicalOpType::Not = op.op_type {
+            let value = self.eval(&op.operands[0])?;
+            return Ok(UVValue::Boolean(!as_bool(
+                &value,
+                op.operands[0].get_span(),
+            )?));
+        }
+
+        for operand in &op.operands {
+            let value = as_bool(&self.eval(operand)?, operand.get_span())?;
+            if short_circuits(&op.op_type, value) {
+                return Ok(UVValue::Boolean(value));
+            }
+        }
+
+        // All operands are `true` for <and> or `false` for <or>
+        Ok(UVValue::Boolean(matches!(op.op_type, LogicalOpType::And)))
+    }
+
+    fn eval_for_loop(&mut self, for_loop: &ForLoop) -> EvalResult {
+        let start = self.eval(&for_loop.start.value)?;
+        let end = self.eval(&for_loop.end.value)?;
+        let step = match &for_loop.step {
+            Some(step) => self.eval(&step.value)?,
+            None => UVValue::Int(1),
+        };
+
+        for (value, span) in [
+            (&start, for_loop.start.span),
+            (&end, for_loop.end.span),
+            (
+                &step,
+                for_loop.step.as_ref().map_or(for_loop.span, |s| s.span),
+            ),
+        ] {
+            if !matches!(value, UVValue::Int(_) | UVValue::Float(_)) {
+                return Err(SpannedError::new(
+                    format!(
+                        "For loop bounds must be numeric, found `{}`",
+                        value.get_type()
+                    ),
+                    span,
+                )
+                .with_code(ErrorCode::InvalidLoopBounds)
+                .into());
+            }
+        }
+
+        let zero = UVValue::Int(0);
+        let ascending = compare(&CompareOpType::Greater, &step, &zero, for_loop.span)?;
+        if compare(&CompareOpType::Equality, &step, &zero, for_loop.span)? {
+            return Err(
+                SpannedError::new("For loop step cannot be zero", for_loop.span)
+                    .with_code(ErrorCode::InvalidLoopBounds)
+                    .into(),
+            );
+        }
+
+        let condition = if ascending {
+            CompareOpType::Less
+        } else {
+            CompareOpType::Greater
+        };
+
+        let mut current = start;
+        while compare(&condition, &current, &end, for_loop.span)? {
+            self.env.push_scope();
+            self.env.define(&for_loop.iterator.value, current.clone());
+            let result = self.eval_block(&for_loop.body.value);
+            self.env.pop_scope();
+            result?;
+
+            current = binary_math(&MathOpType::Sum, &current, &step, for_loop.span)?;
+        }
+        Ok(UVValue::Null)
+    }
+
+    fn call(&mut self, call: &FunctionCall) -> EvalResult {
+        let function = self.functions.get(&call.callee.value).cloned().ok_or(
+            SpannedError::new(
+                format!("Cannot find function `{}`", call.callee.value),
+                call.callee.span,
+            )
+            .with_code(ErrorCode::UnresolvedName),
+        )?;
+
+        if self.env.call_depth() >= MAX_CALL_DEPTH {
+            return Err(SpannedError::new(
+                format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded"),
+                call.span,
+            )
+            .with_code(ErrorCode::CallDepthExceeded)
+            .into());
+        }
+
+        // Arguments are evaluated in the caller frame
+        let mut args: Vec<Option<UVValue>> = vec![None; function.params.len()];
+        for (position, arg) in call.args.iter().enumerate() {
+            let index = match &arg.name {
+                Some(name) => function
+                    .params
+                    .iter()
+                    .position(|p| p.name.value == name.value),
+                None => (position < function.params.len()).then_some(position),
+            }
+            .ok_or(
+                SpannedError::new(
+                    format!("Unexpected argument for function `{}`", function.name.value),
+                    arg.span,
+                )
+                .with_code(ErrorCode::InvalidArguments),
+            )?;
+
+            args[index] = Some(self.eval(&arg.value.value)?);
+        }
+
+        self.env.push_frame();
+        for (param, value) in function.params.iter().zip(args) {
+            let Some(value) = value else {
+                self.env.pop_frame();
+                return Err(SpannedError::new(
+                    format!("Missing argument for parameter `{}`", param.name.value),
+                    call.span,
+                )
+                .with_code(ErrorCode::InvalidArguments)
+                .into());
+            };
+            self.env.define(&param.name.value, value);
+        }
+
+        let result = self.eval_block(&function.body.value);
+        self.env.pop_frame();
+
+        match result {
+            Ok(_) => Ok(UVValue::Null),
+            Err(Interrupt::Return(value)) => Ok(value),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn undefined_variable(name: &str, span: Span) -> Interrupt {
+    SpannedError::new(format!("Cannot find variable `{name}` in this scope"), span)
+        .with_code(ErrorCode::UnresolvedName)
+        .into()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        ast::{gen_main_ast, types::ASTBlockType},
+        interpreter::Interpreter,
+        lexer::Lexer,
+        tokens_parser::TokenParser,
+    };
+
+    /// Programs (content of <main>) and their expected output.
+    /// Shared between execution backends
+    pub(crate) const CASES: &[(&str, &str)] = &[
+        (
+            "<println><str>Hello world!</str></println>",
+            "Hello world!\n",
+        ),
+        (
+            "<println><sum><int>1</int><int>2</int><int>3</int></sum>\
+            <sub><int>1</int><float>0.5</float></sub>\
+            <mul><int>2</int><float>2</float></mul>\
+            <div><int>7</int><int>2</int></div>\
+            <mod><int>7</int><int>2</int></mod>\
+            <sum><str>a</str><str>b</str></sum></println>",
+            "6 0.5 4.0 3 1 ab\n",
+        ),
+        (
+            "<println><eq><int>1</int><float>1.0</float></eq>\
+            <neq><str>a</str><str>b</str></neq>\
+            <lt><int>1</int><int>2</int></lt>\
+            <gte><float>1.5</float><int>2</int></gte>\
+            <and><bool>1</bool><bool>0</bool></and>\
+            <or><bool>0</bool><bool>1</bool></or>\
+            <not><bool>1</bool></not></println>",
+            "true true true false false true false\n",
+        ),
+        (
+            "<let><name>x</name><value><int>1</int></value></let>\
+            <x><sum><x /><int>1</int></sum></x>\
+            <b><let><name>x</name><value><int>10</int></value></let><println><x /></println></b>\
+            <println><x /></println>",
+            "10\n2\n",
+        ),
+        (
+            "<for><iterator>i</iterator><start><int>0</int></start><end><int>6</int></end>\
+            <step><int>2</int></step><body><println><i /></println></body></for>\
+            <for><iterator>i</iterator><start><int>2</int></start><end><int>0</int></end>\
+            <step><sub><int>0</int><int>1</int></sub></step><body><println><i /></println></body></for>",
+            "0\n2\n4\n2\n1\n",
+        ),
+        (
+            "<let><name>i</name><value><int>0</int></value></let>\
+            <while><cond><lt><i /><int>3</int></lt></cond>\
+            <body><i><sum><i /><int>1</int></sum></i></body></while>\
+            <println><i /></println>",
+            "3\n",
+        ),
+        (
+            "<for><iterator>i</iterator><start><int>0</int></start><end><int>3</int></end><body>\
+            <if><cond><eq><i /><int>0</int></eq></cond><then><println><str>zero</str></println></then>\
+            <elif><cond><eq><i /><int>1</int></eq></cond><then><println><str>one</str></println></then></elif>\
+            <else><println><str>many</str></println></else></if></body></for>",
+            "zero\none\nmany\n",
+        ),
+        (
+            "<fn><name>fact</name><params><arg><name>n</name><type><int /></type></arg></params>\
+            <returns><int /></returns><body>\
+            <if><cond><lte><n /><int>1</int></lte></cond><then><return><int>1</int></return></then></if>\
+            <return><mul><n /><call fact><arg><sub><n /><int>1</int></sub></arg></call></mul></return>\
+            </body></fn>\
+            <println><call fact><arg><int>10</int></arg></call></println>",
+            "3628800\n",
+        ),
+        (
+            "<println><call join><arg right><str>b</str></arg><arg left><str>a</str></arg></call></println>\
+            <fn><name>join</name><params><arg><name>left</name><type><str /></type></arg>\
+            <arg><name>right</name><type><str /></type></arg></params><returns><str /></returns>\
+            <body><return><sum><left /><right /></sum></return></body></fn>",
+            "ab\n",
+        ),
+        (
+            "<let><name>x</name><value><b>\
+            <let><name>y</name><value><int>2</int></value></let><mul><y /><y /></mul></b></value></let>\
+            <println><x /><null /></println>",
+            "4 null\n",
+        ),
+    ];
+
+    /// Programs (content of <main>) and their expected runtime error
+    pub(crate) const ERROR_CASES: &[(&str, &str)] = &[
+        ("<div><int>1</int><int>0</int></div>", "Division by zero"),
+        (
+            "<sum><int>9223372036854775807</int><int>1</int></sum>",
+            "Integer overflow",
+        ),
+        (
+            "<for><iterator>i</iterator><start><int>0</int></start><end><int>1</int></end>\
+            <step><int>0</int></step><body></body></for>",
+            "For loop step cannot be zero",
+        ),
+        (
+            "<fn><name>f</name><body><call f></call></body></fn><call f></call>",
+            "Maximum call depth of 256 exceeded",
+        ),
+    ];
+
+    pub(crate) fn parse(main: &str) -> ASTBlockType {
+        let code = format!("<program><main>{main}</main></program>");
+        let node = TokenParser::new(Lexer::new(code).parse()).parse().unwrap();
+        gen_main_ast(&node).unwrap()
+    }
+
+    fn run(main: &str) -> Result<String, String> {
+        let mut interpreter = Interpreter::new(Vec::new());
+        interpreter
+            .run_program(&parse(main))
+            .map_err(|err| err.message().to_owned())?;
+
+        Ok(String::from_utf8(interpreter.into_output()).unwrap())
+    }
+
+    #[test]
+    fn programs() {
+        for (program, expected) in CASES {
+            assert_eq!(run(program).as_deref(), Ok(*expected), "{program}");
+        }
+    }
+
+    #[test]
+    fn runtime_errors() {
+        for (program, expected) in ERROR_CASES {
+            assert_eq!(run(program), Err(expected.to_string()), "{program}");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+
+use crate::{
+    ast::{
+        traits::GetType,
+        types::{CompareOpType, LogicalOpType, MathOpType, UVValue},
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    types::Span,
+};
+
+/// Result of math operation without operands (`<sum />` is 0, `<mul />` is 1)
+pub fn math_identity(op: &MathOpType) -> UVValue {
+    match op {
+        MathOpType::Mul => UVValue::Int(1),
+        _ => UVValue::Int(0),
+    }
+}
+
+/// Apply math operation to a pair of values
+pub fn binary_math(
+    op: &MathOpType,
+    left: &UVValue,
+    right: &UVValue,
+    span: Span,
+) -> Result<UVValue, SpannedError> {
+    match (op, left, right) {
+        (MathOpType::Sum, UVValue::String(a), UVValue::String(b)) => {
+            Ok(UVValue::String(format!("{a}{b}")))
+        }
+        (_, UVValue::Int(a), UVValue::Int(b)) => int_math(op, *a, *b, span),
+        (_, UVValue::Int(_) | UVValue::Float(_), UVValue::Int(_) | UVValue::Float(_)) => {
+            let (a, b) = (as_float(left), as_float(right));
+            Ok(UVValue::Float(match op {
+                MathOpType::Sum => a + b,
+                MathOpType::Sub => a - b,
+                MathOpType::Mul => a * b,
+                MathOpType::Div => a / b,
+                MathOpType::Mod => a % b,
+            }))
+        }
+        _ => Err(SpannedError::new(
+            format!(
+                "`<{op}>` cannot be applied to `{}` and `{}`",
+                left.get_type(),
+                right.get_type()
+            ),
+            span,
+        )
+        .with_code(ErrorCode::TypeMismatch)),
+    }
+}
+
+fn int_math(op: &MathOpType, a: i64, b: i64, span: Span) -> Result<UVValue, SpannedError> {
+    let result = match op {
+        MathOpType::Sum => a.checked_add(b),
+        MathOpType::Sub => a.checked_sub(b),
+        MathOpType::Mul => a.checked_mul(b),
+        MathOpType::Div | MathOpType::Mod if b == 0 => {
+            return Err(
+                SpannedError::new("Division by zero", span).with_code(ErrorCode::DivisionByZero)
+            );
+        }
+        MathOpType::Div => a.checked_div(b),
+        MathOpType::Mod => a.checked_rem(b),
+    };
+
+    result
+        .map(UVValue::Int)
+        .ok_or(SpannedError::new("Integer overflow", span).with_code(ErrorCode::IntegerOverflow))
+}
+
+fn as_float(value: &UVValue) -> f64 {
+    match value {
+        UVValue::Int(i) => *i as f64,
+        UVValue::Float(f) => *f,
+        _ => f64::NAN,
+    }
+}
+
+/// Compare pair of values
+pub fn compare(
+    op: &CompareOpType,
+    left: &UVValue,
+    right: &UVValue,
+    span: Span,
+) -> Result<bool, SpannedError> {
+    let ordering = match (left, right) {
+        (UVValue::Int(a), UVValue::Int(b)) => Some(a.cmp(b)),
+        (UVValue::Int(_) | UVValue::Float(_), UVValue::Int(_) | UVValue::Float(_)) => {
+            as_float(left).partial_cmp(&as_float(right))
+        }
+        (UVValue::String(a), UVValue::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    Ok(match op {
+        CompareOpType::Equality => ordering.map_or(left == right, |o| o == Ordering::Equal),
+        CompareOpType::NotEquality => ordering.map_or(left != right, |o| o != Ordering::Equal),
+        _ => {
+            let ordering = ordering.ok_or(
+                SpannedError::new(
+                    format!(
+                        "`<{op}>` cannot compare `{}` and `{}`",
+                        left.get_type(),
+                        right.get_type()
+                    ),
+                    span,
+                )
+                .with_code(ErrorCode::TypeMismatch),
+            )?;
+
+            match op {
+                CompareOpType::Greater => ordering == Ordering::Greater,
+                CompareOpType::GreaterEquals => ordering != Ordering::Less,
+                CompareOpType::Less => ordering == Ordering::Less,
+                _ => ordering != Ordering::Greater,
+            }
+        }
+    })
+}
+
+/// Get boolean value, that is used as condition or logical operand
+pub fn as_bool(value: &UVValue, span: Span) -> Result<bool, SpannedError> {
+    match value {
+        UVValue::Boolean(b) => Ok(*b),
+        other => Err(SpannedError::new(
+            format!("Expected `bool`, found `{}`", other.get_type()),
+            span,
+        )
+        .with_code(ErrorCode::TypeMismatch)),
+    }
+}
+
+/// Check if logical operation result is known after the operand (short-circuit evaluation)
+pub fn short_circuits(op: &LogicalOpType, operand: bool) -> bool {
+    match op {
+        LogicalOpType::And => !operand,
+        LogicalOpType::Or => operand,
+        LogicalOpType::Not => false,
+    }
+}
@@ -14,19 +14,16 @@ impl<T: Clone + std::cmp::PartialEq> Iter<T> {
 
     /// Get next iterator element with moving of position
     pub fn next(&mut self) -> Option<T> {
-        (self.pos >= self.vec.len())
-            .then_some(None)
-            .unwrap_or_else(|| {
-                self.pos += 1;
-                Some(self.vec[self.pos - 1].clone())
-            })
+        let item = self.vec.get(self.pos).cloned();
+        if item.is_some() {
+            self.pos += 1;
+        }
+        item
     }
 
     /// Get pending iterator element with offset
     pub fn peek(&self, indent: Option<usize>) -> Option<T> {
-        (self.pos + indent.unwrap_or(0) >= self.vec.len())
-            .then(|| None)
-            .unwrap_or_else(|| Some(self.vec[self.pos + indent.unwrap_or(0)].clone()))
+        self.vec.get(self.pos + indent.unwrap_or(0)).cloned()
     }
 
     /// Move iterator back
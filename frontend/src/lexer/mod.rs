@@ -28,29 +28,23 @@ impl Lexer {
 
     pub fn parse(&mut self) -> Vec<UVToken> {
         let mut tokens: Vec<UVToken> = Vec::new();
-        while let Some(_) = self.iter.peek(None) {
+        while self.iter.peek(None).is_some() {
             let iteration_buffer = match self.parse_state {
                 LexerParseState::Default => self.lex_normal_mode(),
-                LexerParseState::ParsingRawStringLiteral(_) => self.lex_raw_mode(),
+                LexerParseState::ParsingRawStringLiteral(_)
+                | LexerParseState::ParsingIndentedStringLiteral(_)
+                | LexerParseState::ParsingEscapedStringLiteral => self.lex_raw_mode(),
             };
 
             tokens.extend(iteration_buffer);
         }
 
         // If literal is a raw – disable trimming
-        let trim_end = if matches!(self.parse_state, LexerParseState::Default) {
-            true
-        } else {
-            false
-        };
-
-        if let Some(lit) = self.finish_consuming_literal(trim_end) {
-            let token = match self.parse_state {
-                LexerParseState::Default => UVLexerTokens::Literal(lit),
-                LexerParseState::ParsingRawStringLiteral(_) => UVLexerTokens::RawString(lit),
-            };
+        if self.parse_state != LexerParseState::Default {
+            tokens.extend(self.finish_consuming_raw_literal(self.iter.pos));
+        } else if let Some(lit) = self.finish_consuming_literal(true) {
             tokens.push(UVToken {
-                token: token,
+                token: UVLexerTokens::Literal(lit),
                 span: Span::new(self.token_start, self.iter.pos),
             });
         }
@@ -76,8 +70,20 @@ impl Lexer {
                             return iteration_buffer;
                         }
 
+                        if let Some(token) = self.check_cdata_and_consume() {
+                            iteration_buffer.push(token);
+                            return iteration_buffer;
+                        }
+
                         self.token_start = self.iter.pos - 1;
-                        if self.iter.peek(None) == Some('/') {
+                        if self.iter.peek(None) == Some('?') {
+                            self.iter.next(); // Consume '?'
+                            iteration_buffer.push(UVToken {
+                                token: UVLexerTokens::InstructionStart,
+                                span: Span::new(self.token_start, self.iter.pos),
+                            });
+                            return iteration_buffer;
+                        } else if self.iter.peek(None) == Some('/') {
                             self.iter.next(); // Consume '/'
                             iteration_buffer.push(UVToken {
                                 token: UVLexerTokens::OpeningAngleBracketSlash,
@@ -90,11 +96,24 @@ impl Lexer {
                             });
                         }
 
-                        if let Some(key) = self.check_opening_raw_str_tag() {
-                            self.parse_state = LexerParseState::ParsingRawStringLiteral(key);
+                        let string_state = if let Some(key) = self.check_opening_raw_str_tag("str")
+                        {
+                            Some(LexerParseState::ParsingRawStringLiteral(key))
+                        } else if let Some(key) = self.check_opening_raw_str_tag("istr") {
+                            Some(LexerParseState::ParsingIndentedStringLiteral(key))
+                        } else if self.check_opening_escaped_str_tag() {
+                            Some(LexerParseState::ParsingEscapedStringLiteral)
+                        } else {
+                            None
+                        };
+
+                        if let Some(state) = string_state {
+                            self.parse_state = state;
                             iteration_buffer.extend([
                                 UVToken {
-                                    token: UVLexerTokens::Literal("str".to_string()),
+                                    token: UVLexerTokens::Literal(
+                                        self.string_tag_name().to_owned(),
+                                    ),
                                     span: Span::new(self.token_start + 1, self.iter.pos - 1),
                                 },
                                 UVToken {
@@ -130,6 +149,36 @@ impl Lexer {
                     _ => {}
                 }
             }
+            '?' if self.iter.peek(None) == Some('>') => {
+                if let Some(str) = self.finish_consuming_literal(true) {
+                    iteration_buffer.push(UVToken {
+                        token: UVLexerTokens::Literal(str),
+                        span: Span::new(self.token_start, self.iter.pos - 1),
+                    });
+                }
+
+                self.iter.next(); // Consume '>'
+                iteration_buffer.push(UVToken {
+                    token: UVLexerTokens::InstructionEnd,
+                    span: Span::new(self.iter.pos - 2, self.iter.pos),
+                });
+            }
+            '=' | '"' | '\'' => {
+                if let Some(str) = self.finish_consuming_literal(true) {
+                    iteration_buffer.push(UVToken {
+                        token: UVLexerTokens::Literal(str),
+                        span: Span::new(self.token_start, self.iter.pos - 1),
+                    });
+                }
+
+                iteration_buffer.push(match ch {
+                    '=' => UVToken {
+                        token: UVLexerTokens::Equals,
+                        span: Span::new(self.iter.pos - 1, self.iter.pos),
+                    },
+                    quote => self.consume_quoted_string(quote),
+                });
+            }
             char if Self::is_valid_literal(char) => {
                 if self.buffer.is_empty() {
                     self.token_start = self.iter.pos - 1;
@@ -166,31 +215,24 @@ impl Lexer {
         self.buffer.push(ch);
         let token_end = self.iter.pos;
 
-        if ch == '<' {
-            if self.check_closing_raw_str_tag() {
-                self.buffer.pop(); // Remove '<' from buffer
-                if let Some(str) = self.finish_consuming_literal(false) {
-                    iteration_buffer.push(UVToken {
-                        token: UVLexerTokens::RawString(str),
-                        span: Span::new(self.token_start, token_end - 1),
-                    });
-                }
-                iteration_buffer.extend([
-                    UVToken {
-                        token: UVLexerTokens::OpeningAngleBracketSlash,
-                        span: Span::new(token_end - 1, token_end + 1),
-                    },
-                    UVToken {
-                        token: UVLexerTokens::Literal("str".to_string()),
-                        span: Span::new(token_end + 1, self.iter.pos - 1),
-                    },
-                    UVToken {
-                        token: UVLexerTokens::ClosingAngleBracket,
-                        span: Span::new(self.iter.pos - 1, self.iter.pos),
-                    },
-                ]);
-                self.parse_state = LexerParseState::Default;
-            }
+        if ch == '<' && self.check_closing_raw_str_tag() {
+            self.buffer.pop(); // Remove '<' from buffer
+            iteration_buffer.extend(self.finish_consuming_raw_literal(token_end - 1));
+            iteration_buffer.extend([
+                UVToken {
+                    token: UVLexerTokens::OpeningAngleBracketSlash,
+                    span: Span::new(token_end - 1, token_end + 1),
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal(self.string_tag_name().to_owned()),
+                    span: Span::new(token_end + 1, self.iter.pos - 1),
+                },
+                UVToken {
+                    token: UVLexerTokens::ClosingAngleBracket,
+                    span: Span::new(self.iter.pos - 1, self.iter.pos),
+                },
+            ]);
+            self.parse_state = LexerParseState::Default;
         }
 
         iteration_buffer
@@ -214,6 +256,97 @@ impl Lexer {
         token
     }
 
+    /// Returns tokens of buffered raw string, that ends at `end`
+    fn finish_consuming_raw_literal(&mut self, end: usize) -> Vec<UVToken> {
+        if let LexerParseState::ParsingIndentedStringLiteral(_) = self.parse_state {
+            let tokens = Self::trim_indent(&self.buffer, self.token_start);
+            self.buffer.clear();
+            return tokens;
+        }
+
+        self.finish_consuming_literal(false)
+            .map(|str| UVToken {
+                token: UVLexerTokens::RawString(str),
+                span: Span::new(self.token_start, end),
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /**
+    Split `<istr>` content, that starts at `start`, to lines.
+    Common indentation and the first and last blank lines are removed
+
+    Every line is a separate token with the span of its remaining chars
+    */
+    fn trim_indent(content: &str, start: usize) -> Vec<UVToken> {
+        let is_blank = |line: &[char]| line.iter().all(|c| c.is_whitespace());
+
+        let mut lines: Vec<(usize, Vec<char>)> = Vec::new();
+        let mut line_start = start;
+        for line in content.split('\n') {
+            let chars = line.strip_suffix('\r').unwrap_or(line).chars().collect();
+            lines.push((line_start, chars));
+            line_start += line.chars().count() + 1; // Skip '\n'
+        }
+
+        if lines.first().is_some_and(|(_, line)| is_blank(line)) {
+            lines.remove(0);
+        }
+        if lines.last().is_some_and(|(_, line)| is_blank(line)) {
+            lines.pop();
+        }
+
+        let indent = lines
+            .iter()
+            .filter(|(_, line)| !is_blank(line))
+            .map(|(_, line)| line.iter().take_while(|c| c.is_whitespace()).count())
+            .min()
+            .unwrap_or(0);
+
+        lines
+            .into_iter()
+            .map(|(line_start, line)| {
+                let skip = indent.min(line.len());
+                UVToken {
+                    token: UVLexerTokens::RawString(line[skip..].iter().collect()),
+                    span: Span::new(line_start + skip, line_start + line.len()),
+                }
+            })
+            .collect()
+    }
+
+    /**
+    Consume attribute value until the closing `quote`
+
+    Values cannot contain `<`, so unterminated quote does not swallow following tags.
+    In this case the quote is returned as unknown token
+    */
+    fn consume_quoted_string(&mut self, quote: char) -> UVToken {
+        let start = self.iter.pos - 1;
+        let mut value = String::new();
+        let mut len = 0;
+        while let Some(ch) = self.iter.peek(Some(len)) {
+            if ch == quote {
+                self.iter.pos += len + 1;
+                return UVToken {
+                    token: UVLexerTokens::QuotedString(value),
+                    span: Span::new(start, self.iter.pos),
+                };
+            }
+            if ch == '<' {
+                break;
+            }
+            value.push(ch);
+            len += 1;
+        }
+
+        UVToken {
+            token: UVLexerTokens::Unknown(quote),
+            span: Span::new(start, start + 1),
+        }
+    }
+
     /// Consume all symbols after <str- and before >
     fn consume_raw_str_label(&mut self) -> Option<String> {
         let mut buffer = String::new();
@@ -227,13 +360,14 @@ impl Lexer {
         None
     }
 
-    /// Check if iterator currently reach <str-xx> tag
-    fn check_opening_raw_str_tag(&mut self) -> Option<Option<String>> {
+    /// Check if iterator currently reach <name> or <name-xx> tag, such as <str-xx>
+    fn check_opening_raw_str_tag(&mut self, name: &str) -> Option<Option<String>> {
         let start_iter_pos = self.iter.pos;
         self.iter.step_back(); // For proper consuming '<'
 
-        if self.iter.starts_with(&['<', 's', 't', 'r']) {
-            self.iter.pos += 4;
+        let pattern: Vec<char> = std::iter::once('<').chain(name.chars()).collect();
+        if self.iter.starts_with(&pattern) {
+            self.iter.pos += pattern.len();
             match self.iter.next() {
                 Some('>') => return Some(None),
                 Some('-') => return Some(self.consume_raw_str_label()),
@@ -245,33 +379,52 @@ impl Lexer {
         None
     }
 
-    /// Check if iterator currently reach </str-xx> tag
+    /// Check if iterator currently reach <estr> tag
+    fn check_opening_escaped_str_tag(&mut self) -> bool {
+        let start_iter_pos = self.iter.pos;
+        self.iter.step_back(); // For proper consuming '<'
+
+        if self.iter.starts_with(&['<', 'e', 's', 't', 'r', '>']) {
+            self.iter.pos += 6;
+            return true;
+        }
+
+        self.iter.pos = start_iter_pos;
+        false
+    }
+
+    /// Get name of the string tag, that is passed to the parser.
+    /// Raw and indented strings are both `str`, as they differ only in lexing
+    fn string_tag_name(&self) -> &'static str {
+        match self.parse_state {
+            LexerParseState::ParsingEscapedStringLiteral => "estr",
+            _ => "str",
+        }
+    }
+
+    /// Check if iterator currently reach closing tag of the lexed string, such as </str-xx>
     fn check_closing_raw_str_tag(&mut self) -> bool {
+        let (name, start_label) = match &self.parse_state {
+            LexerParseState::ParsingRawStringLiteral(label) => ("str", label.clone()),
+            LexerParseState::ParsingIndentedStringLiteral(label) => ("istr", label.clone()),
+            LexerParseState::ParsingEscapedStringLiteral => ("estr", None),
+            LexerParseState::Default => return false,
+        };
+
         let start_iter_pos = self.iter.pos;
         self.iter.step_back(); // For proper consuming '<'
 
-        if self.iter.starts_with(&['<', '/', 's', 't', 'r']) {
-            self.iter.pos += 5;
+        let pattern: Vec<char> = ['<', '/'].into_iter().chain(name.chars()).collect();
+        if self.iter.starts_with(&pattern) {
+            self.iter.pos += pattern.len();
 
             match self.iter.next() {
-                Some('>')
-                    if matches!(
-                        self.parse_state,
-                        LexerParseState::ParsingRawStringLiteral(None)
-                    ) =>
+                Some('>') if start_label.is_none() => return true,
+                Some('-')
+                    if start_label.is_some() && self.consume_raw_str_label() == start_label =>
                 {
                     return true;
                 }
-                Some('-') => {
-                    let label = self.consume_raw_str_label();
-                    if let Some(label) = label
-                        && let LexerParseState::ParsingRawStringLiteral(Some(start_label)) =
-                            &self.parse_state
-                        && start_label.eq(&label)
-                    {
-                        return true;
-                    }
-                }
                 _ => {}
             }
         }
@@ -285,28 +438,65 @@ impl Lexer {
             return false;
         }
 
-        while let Some(_) = self.iter.next() {
+        while self.iter.next().is_some() {
             if self.iter.starts_with(&['-', '-', '>']) {
                 self.iter.pos += 3;
                 return true;
             }
         }
-        return true;
+        true
+    }
+
+    /**
+    Consume `<![CDATA[...]]>` section and get its content as a raw string.
+    Unterminated section lasts until the end of the code.
+
+    Content is a bare literal, so it is a value only inside of scalar tags, such as
+    `<int><![CDATA[5]]></int>`, and it is not an expression by itself.
+    Inside of `<str>`, `<istr>` and `<estr>` sections are not recognized and kept verbatim
+    */
+    fn check_cdata_and_consume(&mut self) -> Option<UVToken> {
+        const START: [char; 8] = ['!', '[', 'C', 'D', 'A', 'T', 'A', '['];
+        if !self.iter.starts_with(&START) {
+            return None;
+        }
+
+        self.iter.pos += START.len();
+        let start = self.iter.pos;
+        let mut content = String::new();
+        let mut end = start;
+        while !self.iter.starts_with(&[']', ']', '>']) {
+            match self.iter.next() {
+                Some(ch) => content.push(ch),
+                None => break,
+            }
+            end = self.iter.pos;
+        }
+        if self.iter.peek(None).is_some() {
+            self.iter.pos += 3; // Consume ']]>'
+        }
+
+        Some(UVToken {
+            token: UVLexerTokens::RawString(content),
+            span: Span::new(start, end),
+        })
     }
 
     fn is_valid_literal(c: char) -> bool {
-        c.is_alphanumeric() || matches!(c, '.' | ',' | '_')
+        c.is_alphanumeric() || matches!(c, '.' | ',' | '_' | '-' | '+')
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        Backend, check,
         lexer::{
             Lexer,
             types::{UVLexerTokens, UVToken},
         },
-        types::Span,
+        run,
+        types::{SourceFile, Span},
     };
 
     fn get_tokens(code: &str) -> Vec<UVLexerTokens> {
@@ -367,6 +557,108 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parse_signed_literal() {
+        assert_eq!(
+            get_tokens("<float>-1e+3</float>"),
+            [
+                UVLexerTokens::OpeningAngleBracket,
+                UVLexerTokens::Literal("float".to_owned()),
+                UVLexerTokens::ClosingAngleBracket,
+                UVLexerTokens::Literal("-1e+3".to_owned()),
+                UVLexerTokens::OpeningAngleBracketSlash,
+                UVLexerTokens::Literal("float".to_owned()),
+                UVLexerTokens::ClosingAngleBracket
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_attributes() {
+        assert_eq!(
+            Lexer::new(r#"<int value="5" flag/>"#.to_owned()).parse(),
+            [
+                UVToken {
+                    token: UVLexerTokens::OpeningAngleBracket,
+                    span: Span::new(0, 1)
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal("int".to_owned()),
+                    span: Span::new(1, 4)
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal("value".to_owned()),
+                    span: Span::new(5, 10)
+                },
+                UVToken {
+                    token: UVLexerTokens::Equals,
+                    span: Span::new(10, 11)
+                },
+                UVToken {
+                    token: UVLexerTokens::QuotedString("5".to_owned()),
+                    span: Span::new(11, 14)
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal("flag".to_owned()),
+                    span: Span::new(15, 19)
+                },
+                UVToken {
+                    token: UVLexerTokens::SelfClosingAngleBracket,
+                    span: Span::new(19, 21)
+                },
+            ]
+        )
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        assert_eq!(
+            get_tokens(r#"<a b="c></a>"#)[2..5],
+            [
+                UVLexerTokens::Literal("b".to_owned()),
+                UVLexerTokens::Equals,
+                UVLexerTokens::Unknown('"'),
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_cdata() {
+        assert_eq!(
+            Lexer::new("<a><![CDATA[ <b> ]] ]]></a>".to_owned()).parse()[3],
+            UVToken {
+                token: UVLexerTokens::RawString(" <b> ]] ".to_owned()),
+                span: Span::new(12, 20)
+            }
+        );
+        assert_eq!(
+            get_tokens("<a><![CDATA[x")[3],
+            UVLexerTokens::RawString("x".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_instruction() {
+        assert_eq!(
+            get_tokens(r#"<?uv version="1.0"?><a>?</a>"#),
+            [
+                UVLexerTokens::InstructionStart,
+                UVLexerTokens::Literal("uv".to_owned()),
+                UVLexerTokens::Literal("version".to_owned()),
+                UVLexerTokens::Equals,
+                UVLexerTokens::QuotedString("1.0".to_owned()),
+                UVLexerTokens::InstructionEnd,
+                UVLexerTokens::OpeningAngleBracket,
+                UVLexerTokens::Literal("a".to_owned()),
+                UVLexerTokens::ClosingAngleBracket,
+                UVLexerTokens::Unknown('?'),
+                UVLexerTokens::OpeningAngleBracketSlash,
+                UVLexerTokens::Literal("a".to_owned()),
+                UVLexerTokens::ClosingAngleBracket
+            ]
+        )
+    }
+
     #[test]
     fn parse_comments() {
         assert_eq!(
@@ -428,6 +720,73 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parse_escaped_str() {
+        assert_eq!(
+            Lexer::new(r"<estr>a\n&lt;/estr></estr>".to_owned()).parse(),
+            [
+                UVToken {
+                    token: UVLexerTokens::OpeningAngleBracket,
+                    span: Span::new(0, 1)
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal("estr".to_owned()),
+                    span: Span::new(1, 5)
+                },
+                UVToken {
+                    token: UVLexerTokens::ClosingAngleBracket,
+                    span: Span::new(5, 6)
+                },
+                UVToken {
+                    token: UVLexerTokens::RawString(r"a\n&lt;/estr>".to_owned()),
+                    span: Span::new(6, 19)
+                },
+                UVToken {
+                    token: UVLexerTokens::OpeningAngleBracketSlash,
+                    span: Span::new(19, 21)
+                },
+                UVToken {
+                    token: UVLexerTokens::Literal("estr".to_owned()),
+                    span: Span::new(21, 25)
+                },
+                UVToken {
+                    token: UVLexerTokens::ClosingAngleBracket,
+                    span: Span::new(25, 26)
+                },
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_indented_str() {
+        let code = "<istr-text>\n    first\n\n      second\n  </istr-text>";
+        assert_eq!(
+            Lexer::new(code.to_owned()).parse()[3..6],
+            [
+                UVToken {
+                    token: UVLexerTokens::RawString("first".to_owned()),
+                    span: Span::new(16, 21)
+                },
+                UVToken {
+                    token: UVLexerTokens::RawString(String::new()),
+                    span: Span::new(22, 22)
+                },
+                UVToken {
+                    token: UVLexerTokens::RawString("  second".to_owned()),
+                    span: Span::new(27, 35)
+                },
+            ]
+        );
+        assert_eq!(
+            get_tokens(code)[6..],
+            [
+                UVLexerTokens::OpeningAngleBracketSlash,
+                UVLexerTokens::Literal("str".to_owned()),
+                UVLexerTokens::ClosingAngleBracket
+            ]
+        );
+    }
+
     #[test]
     fn parse_broken_raw_str() {
         assert_eq!(
@@ -513,4 +872,25 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn cdata_literals() {
+        let program =
+            |body: &str| SourceFile::from_string(format!("<program><main>{body}</main></program>"));
+
+        let source = program("<println><int><![CDATA[5]]></int></println>");
+        let mut output = Vec::new();
+        run(&source, Backend::Vm, &mut output).unwrap();
+        assert_eq!(output, b"5\n");
+
+        // String tags are raw, the section is a part of the string
+        let source = program("<println><str><![CDATA[a<b]]></str></println>");
+        let mut output = Vec::new();
+        run(&source, Backend::Vm, &mut output).unwrap();
+        assert_eq!(output, b"<![CDATA[a<b]]>\n");
+
+        let source = program("<println><![CDATA[a<b]]></println>");
+        let errors = check(&source).unwrap_err();
+        assert_eq!(errors[0].message(), "Unexpected literals inside print");
+    }
 }
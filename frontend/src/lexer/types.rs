@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::types::Span;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,23 +8,33 @@ pub enum UVLexerTokens {
     ClosingAngleBracket,
     SelfClosingAngleBracket,  // />
     OpeningAngleBracketSlash, // </
+    Equals,                   // =
+    InstructionStart,         // <?
+    InstructionEnd,           // ?>
 
     Literal(String),
     RawString(String),
 
+    /// Attribute value in quotes. Quotes are not included
+    QuotedString(String),
+
     Unknown(char),
 }
 
-impl ToString for UVLexerTokens {
-    fn to_string(&self) -> String {
+impl fmt::Display for UVLexerTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            UVLexerTokens::OpeningAngleBracket => "<".to_owned(),
-            UVLexerTokens::ClosingAngleBracket => ">".to_owned(),
-            UVLexerTokens::SelfClosingAngleBracket => "/>".to_owned(),
-            UVLexerTokens::OpeningAngleBracketSlash => "</".to_owned(),
-            UVLexerTokens::Literal(str) => format!("[Literal \"{}\"]", str),
-            UVLexerTokens::RawString(str) => format!("[Raw string \"{}\"]", str),
-            UVLexerTokens::Unknown(ch) => ch.to_string(),
+            UVLexerTokens::OpeningAngleBracket => write!(f, "<"),
+            UVLexerTokens::ClosingAngleBracket => write!(f, ">"),
+            UVLexerTokens::SelfClosingAngleBracket => write!(f, "/>"),
+            UVLexerTokens::OpeningAngleBracketSlash => write!(f, "</"),
+            UVLexerTokens::Equals => write!(f, "="),
+            UVLexerTokens::InstructionStart => write!(f, "<?"),
+            UVLexerTokens::InstructionEnd => write!(f, "?>"),
+            UVLexerTokens::Literal(str) => write!(f, "[Literal \"{}\"]", str),
+            UVLexerTokens::RawString(str) => write!(f, "[Raw string \"{}\"]", str),
+            UVLexerTokens::QuotedString(str) => write!(f, "[Quoted string \"{}\"]", str),
+            UVLexerTokens::Unknown(ch) => write!(f, "{}", ch),
         }
     }
 }
@@ -37,4 +49,10 @@ pub struct UVToken {
 pub enum LexerParseState {
     Default,
     ParsingRawStringLiteral(Option<String>),
+
+    /// Content of `<istr>`, that is split to lines with trimmed common indentation
+    ParsingIndentedStringLiteral(Option<String>),
+
+    /// Content of `<estr>`. Escapes are kept as is and decoded during AST generation
+    ParsingEscapedStringLiteral,
 }
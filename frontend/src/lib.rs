@@ -1,34 +1,273 @@
-use std::path::Path;
+//! Frontend of the Ultraviolet language.
+//!
+//! Every stage of the pipeline is available as a function over [`SourceFile`].
+//! Functions return values and diagnostics and never print anything,
+//! errors can be rendered with [`ErrorRenderer`] or exported with [`diagnostic_to_json`]
+//! and [`diagnostic_to_sarif`].
+//!
+//! Stages themselves are internal, only the pipeline and types of its results are public
+
+use std::io::Write;
 
 use crate::{
-    ast::gen_main_ast, errors::error_renderer::ErrorRenderer, lexer::Lexer,
-    tokens_parser::TokenParser, types::SourceFile,
+    analyzer::{resolver::Resolver, type_checker::TypeChecker},
+    ast::{check_instructions, gen_main_ast},
+    interpreter::Interpreter,
+    lexer::Lexer,
+    tokens_parser::TokenParser,
 };
-use anyhow::Result;
 
+mod analyzer;
 mod ast;
 mod errors;
+mod interpreter;
 mod iterator;
 mod lexer;
+mod repl;
 mod tokens_parser;
 mod types;
+mod vm;
+
+pub use crate::{
+    ast::types::{ASTBlockType, UVValue},
+    errors::{
+        Severity, SpannedError,
+        codes::ErrorCode,
+        error_renderer::ErrorRenderer,
+        json::diagnostic_to_json,
+        sarif::{diagnostic_to_sarif, sarif_log},
+    },
+    lexer::types::UVToken,
+    repl::{Repl, ReplOutput},
+    tokens_parser::types::UVParseNode,
+    types::SourceFile,
+    vm::{disassembler::disassemble, instructions::Program, machine::Machine},
+};
+
+/// Errors found in the source
+pub type Diagnostics = Vec<SpannedError>;
+
+/// Value of the stage, that passed, with warnings found on the way
+#[derive(Debug)]
+pub struct Output<T> {
+    pub value: T,
+
+    /// Diagnostics, that are reported, but do not fail the stage
+    pub warnings: Diagnostics,
+}
+
+impl<T> Output<T> {
+    /// Run the next stage. Warnings are kept, even if the stage fails
+    pub fn and_then<U>(
+        self,
+        stage: impl FnOnce(T) -> Result<U, Diagnostics>,
+    ) -> Result<Output<U>, Diagnostics> {
+        match stage(self.value) {
+            Ok(value) => Ok(Output {
+                value,
+                warnings: self.warnings,
+            }),
+            Err(mut errors) => {
+                errors.extend(self.warnings);
+                Err(errors)
+            }
+        }
+    }
+}
+
+/// Program execution strategy
+#[derive(Debug, Clone, Copy)]
+pub enum Backend {
+    /// Evaluate AST directly
+    Interpreter,
 
-pub fn process(file_path: &Path) -> Result<()> {
-    let source = SourceFile::load(file_path)?;
+    /// Compile AST to bytecode and run it on the stack machine
+    Vm,
+}
+
+/// Split source into tokens
+pub fn tokenize(source: &SourceFile) -> Vec<UVToken> {
+    Lexer::new(source.code.clone()).parse()
+}
+
+/// Get parse tree of the source with all syntax errors
+pub fn parse(source: &SourceFile) -> Result<UVParseNode, Diagnostics> {
+    match TokenParser::new(tokenize(source)).parse_with_recovery() {
+        (Some(node), errors) if errors.is_empty() => Ok(node),
+        (_, errors) => Err(errors),
+    }
+}
 
-    let parse = || {
-        let mut lexer = Lexer::new(source.code.clone());
-        let tokens = lexer.parse();
+/**
+Get AST of the source without semantic checks
 
-        let mut token_parser = TokenParser::new(tokens);
-        let parse_tree = token_parser.parse()?;
-        gen_main_ast(&parse_tree)
+AST is generated even for a damaged parse tree, so errors of both stages are reported together.
+Unknown processing instructions are reported as warnings
+*/
+pub fn parse_ast(source: &SourceFile) -> Result<Output<ASTBlockType>, Diagnostics> {
+    let (node, mut errors) = TokenParser::new(tokenize(source)).parse_with_recovery();
+    let Some(node) = node else {
+        return Err(errors);
     };
 
-    match parse() {
-        Ok(parse_tree) => println!("{:?}", parse_tree),
-        Err(err) => eprintln!("{}", err.display_with_source(&source)),
+    let warnings = check_instructions(&node);
+    match gen_main_ast(&node) {
+        Ok(ast) if errors.is_empty() => {
+            return Ok(Output {
+                value: ast,
+                warnings,
+            });
+        }
+        Ok(_) => {}
+        Err(ast_errors) => errors.extend(ast_errors),
     }
+    errors.extend(warnings);
+    Err(errors)
+}
+
+/// Get AST of the source, that passed name resolution and type checking
+pub fn check(source: &SourceFile) -> Result<Output<ASTBlockType>, Diagnostics> {
+    parse_ast(source)?.and_then(|ast| {
+        let mut symbols = Resolver::new().resolve_program(&ast)?;
+        TypeChecker::new(&mut symbols).check_program(&ast)?;
+        Ok(ast)
+    })
+}
+
+/// Check the source and compile it to bytecode
+pub fn compile(source: &SourceFile) -> Result<Output<Program>, Diagnostics> {
+    check(source)?.and_then(|ast| vm::compile(&ast).map_err(|err| vec![err]))
+}
 
-    Ok(())
+/// Check and execute the source. Output of the program is written to `output`
+pub fn run<W: Write>(
+    source: &SourceFile,
+    backend: Backend,
+    output: W,
+) -> Result<Output<UVValue>, Diagnostics> {
+    check(source)?.and_then(|ast| {
+        let result = match backend {
+            Backend::Interpreter => Interpreter::new(output).run_program(&ast),
+            Backend::Vm => vm::compile(&ast).and_then(|program| Machine::new(output).run(&program)),
+        };
+        result.map_err(|err| vec![err])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Backend,
+        ast::types::UVValue,
+        check,
+        errors::{codes::ErrorCode, error_renderer::ErrorRenderer},
+        lexer::types::UVLexerTokens,
+        parse, parse_ast, run, tokenize,
+        types::SourceFile,
+    };
+
+    const PROGRAM: &str = "<program><main>\n\
+        <let><name>x</name><value><int>2</int></value></let>\n\
+        <println><x /></println>\n\
+        <mul><x /><int>21</int></mul>\n\
+        </main></program>";
+
+    #[test]
+    fn pipeline_stages() {
+        let source = SourceFile::from_string(PROGRAM);
+
+        let tokens = tokenize(&source);
+        assert_eq!(tokens[0].token, UVLexerTokens::OpeningAngleBracket);
+        assert_eq!(
+            tokens[1].token,
+            UVLexerTokens::Literal("program".to_owned())
+        );
+        assert_eq!(parse(&source).unwrap().name, "program");
+        assert!(check(&source).is_ok());
+    }
+
+    #[test]
+    fn run_backends() {
+        let source = SourceFile::from_string(PROGRAM);
+
+        for backend in [Backend::Interpreter, Backend::Vm] {
+            let mut output = Vec::new();
+            assert_eq!(
+                run(&source, backend, &mut output).unwrap().value,
+                UVValue::Int(42)
+            );
+            assert_eq!(output, b"2\n");
+        }
+    }
+
+    #[test]
+    fn warnings_do_not_fail() {
+        let source = SourceFile::from_string(format!("<?xml version=\"1.0\"?>{PROGRAM}"));
+
+        let warnings = check(&source).unwrap().warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code(), Some(ErrorCode::UnknownInstruction));
+
+        for backend in [Backend::Interpreter, Backend::Vm] {
+            let mut output = Vec::new();
+            let result = run(&source, backend, &mut output).unwrap();
+            assert_eq!(result.warnings.len(), 1);
+            assert_eq!(output, b"2\n");
+        }
+
+        // Warnings are reported after errors of the failed stage
+        let source =
+            SourceFile::from_string("<?xml version=\"1.0\"?><program><main><x /></main></program>");
+        let diagnostics = check(&source).unwrap_err();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].is_error());
+        assert!(!diagnostics[1].is_error());
+    }
+
+    #[test]
+    fn diagnostics() {
+        let source = SourceFile::from_string(
+            "<program><main><println><x /><y /></println></main></program>",
+        );
+
+        let errors = check(&source).unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|err| err.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Cannot find variable `x` in this scope",
+                "Cannot find variable `y` in this scope"
+            ]
+        );
+        assert!(
+            errors[0]
+                .display_with_source(&source)
+                .contains("<string>:1:24")
+        );
+    }
+
+    #[test]
+    fn collected_diagnostics() {
+        let source = SourceFile::from_string(
+            "<program><main>\n\
+            <let><name>1x</name><value><int>1</int></value></let>\n\
+            <println><int>1</int>?</println>\n\
+            <let><name>y</name></let>\n\
+            <b><int>2</int></c>\n\
+            </main></program>",
+        );
+
+        let errors = parse_ast(&source).unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|err| err.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unexpected token: `?`",
+                "Unexpected closing tag `c`. Expected `b`",
+                "`1x` is not a valid name for variable",
+                "Variable must be initialized"
+            ]
+        );
+        assert_eq!(parse(&source).unwrap_err().len(), 2);
+    }
 }
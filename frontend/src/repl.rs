@@ -0,0 +1,223 @@
+use std::{io::Write, path::Path};
+
+use crate::{
+    analyzer::resolver::Resolver,
+    ast::{AstGenerator, gen_statements_ast, types::UVValue},
+    errors::error_renderer::ErrorRenderer,
+    interpreter::Interpreter,
+    lexer::Lexer,
+    tokens_parser::TokenParser,
+    types::SourceFile,
+};
+
+/// Result of the REPL input line
+#[derive(Debug, PartialEq)]
+pub enum ReplOutput {
+    /// Tags are not balanced yet, more input is required
+    Pending,
+
+    /// Entry was evaluated to the value of its last statement
+    Value(UVValue),
+
+    /// Rendered errors of the entry
+    Error(String),
+}
+
+/// Interactive session. Variables and functions are kept between entries
+pub struct Repl<W: Write> {
+    interpreter: Interpreter<W>,
+
+    /// Shared by all entries, so ids of their nodes never collide in the resolver
+    generator: AstGenerator,
+
+    /// Names of all previous entries, so each entry is checked before execution
+    resolver: Resolver,
+
+    /// Input of the unfinished entry
+    buffer: String,
+
+    /// Count of finished entries, used to name virtual sources
+    entries: usize,
+}
+
+impl<W: Write> Repl<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            interpreter: Interpreter::new(output),
+            generator: AstGenerator::default(),
+            resolver: Resolver::new(),
+            buffer: String::new(),
+            entries: 0,
+        }
+    }
+
+    /// Check if the current entry waits for more lines
+    pub fn is_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Add line to the current entry and evaluate it, once all tags are closed
+    pub fn feed_line(&mut self, line: &str) -> ReplOutput {
+        if !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(line);
+
+        let mut token_parser = TokenParser::new(Lexer::new(self.buffer.clone()).parse());
+        let nodes = token_parser.parse_all();
+
+        // Unclosed tags are the only problem, so wait for the rest of the entry
+        if let Err(errors) = &nodes
+            && errors.len() == 1
+            && token_parser.reached_eof()
+        {
+            return ReplOutput::Pending;
+        }
+
+        let code = std::mem::take(&mut self.buffer);
+        self.entries += 1;
+        let name = format!("<repl:{}>", self.entries);
+        let source = SourceFile::new(Path::new(&name), code);
+
+        let checkpoint = self.generator.checkpoint();
+        let result = nodes
+            .and_then(|nodes| gen_statements_ast(&mut self.generator, &nodes))
+            .and_then(|statements| {
+                self.resolver.resolve_entry(&statements)?;
+                Ok(statements)
+            })
+            // Names of the rejected entry are discarded by the resolver as well
+            .inspect_err(|_| self.generator.rollback(checkpoint))
+            .and_then(|statements| {
+                self.interpreter
+                    .run_statements(&statements)
+                    .map_err(|err| vec![err])
+            });
+
+        match result {
+            Ok(value) => ReplOutput::Value(value),
+            Err(errors) => ReplOutput::Error(
+                errors
+                    .iter()
+                    .map(|err| err.display_with_source(&source))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::types::UVValue,
+        repl::{Repl, ReplOutput},
+    };
+
+    #[test]
+    fn multiline_entry() {
+        let mut repl = Repl::new(Vec::new());
+
+        assert_eq!(repl.feed_line("<let><name>x</name>"), ReplOutput::Pending);
+        assert!(repl.is_pending());
+        assert_eq!(
+            repl.feed_line("<value><int>2</int></value></let>"),
+            ReplOutput::Value(UVValue::Null)
+        );
+        assert!(!repl.is_pending());
+
+        assert_eq!(
+            repl.feed_line("<mul><x /><int>21</int></mul>"),
+            ReplOutput::Value(UVValue::Int(42))
+        );
+    }
+
+    #[test]
+    fn functions_between_entries() {
+        let mut repl = Repl::new(Vec::new());
+
+        repl.feed_line(
+            "<fn><name>twice</name><params><arg><name>n</name><type><int /></type></arg></params>\
+            <body><return><sum><n /><n /></sum></return></body></fn>",
+        );
+        repl.feed_line("<println><call twice><arg><int>4</int></arg></call></println>");
+
+        assert_eq!(
+            String::from_utf8(repl.interpreter.into_output()).unwrap(),
+            "8\n"
+        );
+    }
+
+    #[test]
+    fn errors_do_not_break_session() {
+        let mut repl = Repl::new(Vec::new());
+
+        repl.feed_line("<let><name>x</name><value><int>1</int></value></let>");
+        let ReplOutput::Error(err) = repl.feed_line("<y />") else {
+            panic!("Expected error");
+        };
+        assert!(err.contains("Cannot find variable `y` in this scope"));
+        assert!(err.contains("<repl:2>:1:0"));
+        assert!(err.contains("1 | <y />"));
+
+        let ReplOutput::Error(err) = repl.feed_line("<a></b>") else {
+            panic!("Expected error");
+        };
+        assert!(err.contains("Unexpected closing tag `b`. Expected `a`"));
+
+        assert_eq!(repl.feed_line("<x />"), ReplOutput::Value(UVValue::Int(1)));
+    }
+
+    #[test]
+    fn names_are_checked_between_entries() {
+        let mut repl = Repl::new(Vec::new());
+
+        repl.feed_line("<let name=\"x\" const><value><int>1</int></value></let>");
+        let ReplOutput::Error(err) = repl.feed_line("<x><int>2</int></x>") else {
+            panic!("Expected error");
+        };
+        assert!(err.contains("Cannot assign to constant `x`"));
+        assert!(err.contains("`x` is declared as constant here"));
+
+        // Rejected entry is not executed
+        assert!(matches!(
+            repl.feed_line("<println><x /></println><call f></call>"),
+            ReplOutput::Error(_)
+        ));
+        repl.feed_line("<println><x /></println>");
+
+        // Later entries can redefine names
+        repl.feed_line("<let name=\"x\"><value><int>3</int></value></let>");
+        assert_eq!(
+            repl.feed_line("<x><int>4</int></x>"),
+            ReplOutput::Value(UVValue::Null)
+        );
+        repl.feed_line("<println><x /></println>");
+
+        assert_eq!(
+            String::from_utf8(repl.interpreter.into_output()).unwrap(),
+            "1\n4\n"
+        );
+    }
+
+    #[test]
+    fn bound_group_name() {
+        let mut repl = Repl::new(Vec::new());
+
+        assert_eq!(
+            repl.feed_line("<b><int>1</int></b>"),
+            ReplOutput::Value(UVValue::Int(1))
+        );
+
+        // Rejected entry does not bind `b`
+        repl.feed_line(r#"<let name="b"><value><int>2</int></value></let><y />"#);
+        assert_eq!(
+            repl.feed_line("<b><int>3</int></b>"),
+            ReplOutput::Value(UVValue::Int(3))
+        );
+
+        repl.feed_line(r#"<let name="b"><value><int>4</int></value></let>"#);
+        repl.feed_line("<b><int>5</int></b>");
+        assert_eq!(repl.feed_line("<b />"), ReplOutput::Value(UVValue::Int(5)));
+    }
+}
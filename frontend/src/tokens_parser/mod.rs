@@ -1,18 +1,35 @@
 pub mod types;
 
 use crate::{
-    errors::SpannedError,
+    errors::{SpannedError, codes::ErrorCode},
     iterator::Iter,
     lexer::types::{UVLexerTokens, UVToken},
-    tokens_parser::types::{UVParseBody, UVParseNode, UVParseState},
+    tokens_parser::types::{UVAttribute, UVInstruction, UVParseBody, UVParseNode, UVParseState},
     types::{Span, Spanned},
 };
 
 /**
 Parses a tokens flow to a parse tree
+
+Parser recovers from errors: unexpected tokens are skipped, mismatched closing
+tags are resynchronized with the nearest open tag. Damaged nodes are kept in the
+tree with [`UVParseBody::Error`] markers and all errors are collected
 */
 pub struct TokenParser {
     iter: Iter<UVToken>,
+
+    /// Tokens ended before all tags were closed
+    reached_eof: bool,
+
+    /// Names of currently open tags, from the root to the innermost
+    open_tags: Vec<String>,
+
+    /// Position of the last reported mismatched closing tag. Parents, that are
+    /// resynchronized with the same tag, do not report it again
+    reported_closing_tag: Option<usize>,
+
+    /// Errors collected during parsing
+    errors: Vec<SpannedError>,
 }
 
 impl TokenParser {
@@ -20,130 +37,407 @@ impl TokenParser {
     pub fn new(tokens: Vec<UVToken>) -> Self {
         Self {
             iter: Iter::from(tokens),
+            reached_eof: false,
+            open_tags: Vec::new(),
+            reported_closing_tag: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Parse all sibling tags until the end of tokens
+    pub fn parse_all(&mut self) -> Result<Vec<UVParseNode>, Vec<SpannedError>> {
+        let mut nodes = Vec::new();
+        while self.iter.peek(None).is_some() {
+            nodes.extend(self.parse_root());
+        }
+
+        match self.errors.is_empty() {
+            true => Ok(nodes),
+            false => Err(std::mem::take(&mut self.errors)),
         }
     }
 
-    /// Parse and get Parse Tree
+    /// Check if tokens ended before all tags were closed
+    pub fn reached_eof(&self) -> bool {
+        self.reached_eof
+    }
+
+    /// Parse and get Parse Tree. Returns the first found error
+    #[cfg(test)]
     pub fn parse(&mut self) -> Result<UVParseNode, SpannedError> {
+        let (node, mut errors) = self.parse_with_recovery();
+        match node {
+            Some(node) if errors.is_empty() => Ok(node),
+            _ => Err(errors.remove(0)),
+        }
+    }
+
+    /**
+    Parse and get Parse Tree with all found errors
+
+    Tree may be partial and contain error markers, when errors are not empty.
+    Returns `None` if there is no tag in tokens
+    */
+    pub fn parse_with_recovery(&mut self) -> (Option<UVParseNode>, Vec<SpannedError>) {
+        let node = self.parse_root();
+        (node, std::mem::take(&mut self.errors))
+    }
+
+    /// Record error and continue parsing
+    fn report(&mut self, code: ErrorCode, message: impl Into<String>, span: Span) {
+        self.errors
+            .push(SpannedError::new(message, span).with_code(code));
+    }
+
+    /// Record error for the token, that is not allowed in the current state
+    fn report_unexpected(&mut self, token: &UVToken) {
+        let message = match &token.token {
+            UVLexerTokens::OpeningAngleBracket => "Unexpected `<` token".to_owned(),
+            UVLexerTokens::ClosingAngleBracket => "Unexpected `>` token".to_owned(),
+            UVLexerTokens::SelfClosingAngleBracket => "Unexpected `/>` token".to_owned(),
+            UVLexerTokens::OpeningAngleBracketSlash => "Unexpected `</` token".to_owned(),
+            UVLexerTokens::Equals => "Unexpected `=` token".to_owned(),
+            UVLexerTokens::InstructionStart => "Unexpected `<?` token".to_owned(),
+            UVLexerTokens::InstructionEnd => "Unexpected `?>` token".to_owned(),
+            UVLexerTokens::Literal(lit) | UVLexerTokens::RawString(lit) => {
+                format!("Unexpected literal `{}`", lit)
+            }
+            UVLexerTokens::QuotedString(str) => format!("Unexpected quoted string \"{}\"", str),
+            UVLexerTokens::Unknown(ch) => format!("Unexpected token: `{}`", ch),
+        };
+        self.report(ErrorCode::UnexpectedToken, message, token.span);
+    }
+
+    /// Record error for the tag name or attribute key with a sign character.
+    /// Signs are lexed as a part of literals only for numbers, such as `-5` or `1e+3`
+    fn check_name(&mut self, tag: &mut UVParseNode, kind: &str, name: &str, span: Span) {
+        if let Some(sign) = name.chars().find(|c| matches!(c, '-' | '+')) {
+            self.report(
+                ErrorCode::InvalidName,
+                format!("{kind} `{name}` cannot contain `{sign}`"),
+                span,
+            );
+            tag.children.push(UVParseBody::Error(span));
+        }
+    }
+
+    /// Parse tag with processing instructions before it
+    fn parse_root(&mut self) -> Option<UVParseNode> {
+        let mut instructions = Vec::new();
+        while self
+            .iter
+            .peek(None)
+            .is_some_and(|token| token.token == UVLexerTokens::InstructionStart)
+        {
+            instructions.extend(self.parse_instruction());
+        }
+
+        let mut node = self.parse_node()?;
+        node.instructions = instructions;
+        Some(node)
+    }
+
+    /// Parse processing instruction `<?target ...?>`. Header is parsed as a tag header
+    fn parse_instruction(&mut self) -> Option<UVInstruction> {
+        // Target is the token right after `<?`
+        let target_span = self
+            .iter
+            .peek(Some(1))
+            .map(|token| token.span)
+            .unwrap_or_default();
+        let node = self.parse_node()?;
+
+        Some(UVInstruction {
+            target: Spanned::new(node.name, target_span),
+            attributes: node.attributes,
+            span: node.span,
+        })
+    }
+
+    /// Parse single tag with its children
+    fn parse_node(&mut self) -> Option<UVParseNode> {
         let mut parse_state = UVParseState::Unknown;
         let mut tag = UVParseNode {
             name: String::new(),
             children: Vec::new(),
             self_closing: false,
-            extra_param: String::new(),
+            attributes: Vec::new(),
+            instructions: Vec::new(),
             span: Span::default(),
         };
 
+        let mut name_span = Span::default();
         let mut closing_tag_name = String::new();
+        let mut closing_tag_span = Span::default();
+
+        // Position of `</` token of the current closing tag, to resynchronize from it
+        let mut closing_tag_pos = 0;
+
+        // Mismatched closing tag was already reported for this node
+        let mut mismatched = false;
+
+        // Node is a processing instruction `<?target ...?>`
+        let mut is_instruction = false;
+
         while let Some(token) = self.iter.next() {
-            match &token.token {
-                UVLexerTokens::OpeningAngleBracket => match parse_state {
-                    UVParseState::Unknown => {
-                        parse_state = UVParseState::TagName;
-                        tag.span.start = token.span.start;
-                    }
-                    UVParseState::TagBody => {
-                        self.iter.step_back();
-                        tag.children.push(UVParseBody::Tag(Box::new(self.parse()?)));
+            match (&token.token, &parse_state) {
+                (UVLexerTokens::OpeningAngleBracket, UVParseState::Unknown) => {
+                    parse_state = UVParseState::TagName;
+                    tag.span.start = token.span.start;
+                }
+                (UVLexerTokens::InstructionStart, UVParseState::Unknown) => {
+                    parse_state = UVParseState::TagName;
+                    tag.span.start = token.span.start;
+                    is_instruction = true;
+                }
+                (
+                    UVLexerTokens::InstructionEnd,
+                    UVParseState::Attributes | UVParseState::AttributeKey,
+                ) if is_instruction => {
+                    tag.self_closing = true;
+                    tag.span.end = token.span.end;
+                    return Some(tag);
+                }
+
+                // Instruction can be closed only with `?>`. Consider the instruction finished
+                (
+                    UVLexerTokens::ClosingAngleBracket
+                    | UVLexerTokens::SelfClosingAngleBracket
+                    | UVLexerTokens::OpeningAngleBracket
+                    | UVLexerTokens::OpeningAngleBracketSlash
+                    | UVLexerTokens::InstructionStart,
+                    UVParseState::TagName | UVParseState::Attributes | UVParseState::AttributeKey,
+                ) if is_instruction => {
+                    self.report(
+                        ErrorCode::UnexpectedToken,
+                        "Processing instruction must be closed with `?>`",
+                        token.span,
+                    );
+                    match token.token {
+                        UVLexerTokens::ClosingAngleBracket
+                        | UVLexerTokens::SelfClosingAngleBracket => {
+                            tag.span.end = token.span.end;
+                        }
+                        _ => {
+                            self.iter.step_back();
+                            tag.span.end = token.span.start;
+                        }
                     }
-                    _ => {
-                        return Err(SpannedError::new("Unexpected `<` token", token.span));
+                    return Some(tag);
+                }
+
+                // Instructions are allowed only before the root tag
+                (UVLexerTokens::InstructionStart, UVParseState::TagBody) => {
+                    self.iter.step_back();
+                    if let Some(instruction) = self.parse_instruction() {
+                        self.report(
+                            ErrorCode::InvalidProgramStructure,
+                            "Processing instructions are allowed only before the root tag",
+                            instruction.span,
+                        );
+                        tag.children.push(UVParseBody::Error(instruction.span));
                     }
-                },
-                UVLexerTokens::ClosingAngleBracket => match parse_state {
-                    UVParseState::ClosingAngleBracketOpeningTag | UVParseState::ExtraParam => {
-                        parse_state = UVParseState::TagBody
+                }
+                (UVLexerTokens::OpeningAngleBracket, UVParseState::TagBody) => {
+                    self.iter.step_back();
+                    self.open_tags.push(tag.name.clone());
+                    let child = self.parse_node();
+                    self.open_tags.pop();
+                    tag.children
+                        .extend(child.map(|child| UVParseBody::Tag(Box::new(child))));
+                }
+                (
+                    UVLexerTokens::ClosingAngleBracket,
+                    UVParseState::Attributes | UVParseState::AttributeKey,
+                ) => parse_state = UVParseState::TagBody,
+                (
+                    UVLexerTokens::ClosingAngleBracket,
+                    UVParseState::ClosingAngleBracketClosingTag,
+                ) => {
+                    if tag.name.eq(&closing_tag_name) {
+                        tag.span.end = token.span.end;
+                        return Some(tag);
                     }
-                    UVParseState::ClosingAngleBracketClosingTag => {
-                        if tag.name.ne(&closing_tag_name) {
-                            return Err(SpannedError::new(
+
+                    let span = closing_tag_span;
+                    let closes_ancestor = self.open_tags.contains(&closing_tag_name);
+                    let already_reported = self.reported_closing_tag == Some(closing_tag_pos)
+                        || (mismatched && closes_ancestor);
+                    if !already_reported {
+                        self.errors.push(
+                            SpannedError::new(
                                 format!(
                                     "Unexpected closing tag `{}`. Expected `{}`",
                                     closing_tag_name, tag.name
                                 ),
-                                Span::new(
-                                    token.span.start - closing_tag_name.len(),
-                                    token.span.end - 1,
-                                ),
-                            ));
-                        }
-
-                        tag.span.end = token.span.end;
-                        return Ok(tag);
-                    }
-                    _ => {
-                        return Err(SpannedError::new("Unexpected `>` token", token.span));
-                    }
-                },
-                UVLexerTokens::SelfClosingAngleBracket => match parse_state {
-                    UVParseState::ClosingAngleBracketOpeningTag | UVParseState::ExtraParam => {
-                        tag.self_closing = true;
-                        tag.span.end = token.span.end;
-                        return Ok(tag);
-                    }
-                    _ => {
-                        return Err(SpannedError::new("Unexpected `/>` token", token.span));
-                    }
-                },
-                UVLexerTokens::OpeningAngleBracketSlash => match parse_state {
-                    UVParseState::TagBody => parse_state = UVParseState::ClosingTagName,
-                    _ => {
-                        return Err(SpannedError::new("Unexpected `</` token", token.span));
-                    }
-                },
-                UVLexerTokens::Literal(lit) | UVLexerTokens::RawString(lit) => match parse_state {
-                    UVParseState::TagName => {
-                        tag.name = lit.to_owned();
-                        parse_state = UVParseState::ExtraParam;
-                    }
-                    UVParseState::ExtraParam => {
-                        parse_state = UVParseState::ClosingAngleBracketOpeningTag;
-                        tag.extra_param = lit.to_owned();
+                                span,
+                            )
+                            .with_code(ErrorCode::MismatchedClosingTag)
+                            .with_primary_label("closing tag here")
+                            .with_label(name_span, "opening tag here"),
+                        );
                     }
+                    mismatched = true;
+                    self.reported_closing_tag = Some(closing_tag_pos);
+                    tag.children.push(UVParseBody::Error(span));
 
-                    UVParseState::TagBody => {
-                        tag.children.push(UVParseBody::String(Spanned {
-                            value: lit.to_owned(),
-                            span: Span::new(token.span.start, token.span.end),
-                        }));
+                    // Closing tag belongs to one of the parents, so leave it for them
+                    if closes_ancestor {
+                        self.iter.pos = closing_tag_pos;
+                        tag.span.end = self.iter.vec[closing_tag_pos].span.start;
+                        return Some(tag);
                     }
-                    UVParseState::ClosingTagName => {
-                        parse_state = UVParseState::ClosingAngleBracketClosingTag;
-                        closing_tag_name = lit.to_owned();
+                    parse_state = UVParseState::TagBody;
+                }
+                (
+                    UVLexerTokens::SelfClosingAngleBracket,
+                    UVParseState::Attributes | UVParseState::AttributeKey,
+                ) => {
+                    tag.self_closing = true;
+                    tag.span.end = token.span.end;
+                    return Some(tag);
+                }
+                (UVLexerTokens::OpeningAngleBracketSlash, UVParseState::TagBody) => {
+                    parse_state = UVParseState::ClosingTagName;
+                    closing_tag_pos = self.iter.pos - 1;
+                }
+                (
+                    UVLexerTokens::Literal(lit) | UVLexerTokens::RawString(lit),
+                    UVParseState::TagName,
+                ) => {
+                    self.check_name(&mut tag, "Tag name", lit, token.span);
+                    tag.name = lit.to_owned();
+                    name_span = token.span;
+                    parse_state = UVParseState::Attributes;
+                }
+                (
+                    UVLexerTokens::Literal(lit),
+                    UVParseState::Attributes | UVParseState::AttributeKey,
+                ) => {
+                    self.check_name(&mut tag, "Attribute key", lit, token.span);
+                    if let Some(first) = tag.get_attribute(lit) {
+                        let first_span = first.span;
+                        self.errors.push(
+                            SpannedError::new(format!("Duplicate attribute `{lit}`"), token.span)
+                                .with_code(ErrorCode::InvalidAttribute)
+                                .with_label(first_span, "first defined here"),
+                        );
+                        tag.children.push(UVParseBody::Error(token.span));
                     }
-                    _ => {
-                        return Err(SpannedError::new(
-                            format!("Unexpected literal `{}`", lit),
-                            token.span,
-                        ));
+                    tag.attributes.push(UVAttribute {
+                        key: Spanned::new(lit.to_owned(), token.span),
+                        value: None,
+                        span: token.span,
+                    });
+                    parse_state = UVParseState::AttributeKey;
+                }
+                (UVLexerTokens::Equals, UVParseState::AttributeKey) => {
+                    parse_state = UVParseState::AttributeValue;
+                }
+                (UVLexerTokens::QuotedString(value), UVParseState::AttributeValue) => {
+                    // State guarantees, that the key was pushed just before
+                    if let Some(attr) = tag.attributes.last_mut() {
+                        let content = Span::new(token.span.start + 1, token.span.end - 1);
+                        attr.value = Some(Spanned::new(value.to_owned(), content));
+                        attr.span.end = token.span.end;
                     }
-                },
-                UVLexerTokens::Unknown(ch) => {
-                    return Err(SpannedError::new(
-                        format!("Unexpected token: `{}`", ch),
+                    parse_state = UVParseState::Attributes;
+                }
+
+                // Missing value is reported and the token is parsed as a part of the header
+                (_, UVParseState::AttributeValue) => {
+                    let key = tag.attributes.last().map(|attr| attr.key.value.clone());
+                    self.report(
+                        ErrorCode::InvalidAttribute,
+                        format!(
+                            "Expected quoted value of `{}` attribute",
+                            key.unwrap_or_default()
+                        ),
                         token.span,
-                    ));
+                    );
+                    tag.children.push(UVParseBody::Error(token.span));
+                    self.iter.step_back();
+                    parse_state = UVParseState::Attributes;
+                }
+                (
+                    UVLexerTokens::Literal(lit) | UVLexerTokens::RawString(lit),
+                    UVParseState::TagBody,
+                ) => {
+                    tag.children.push(UVParseBody::String(Spanned {
+                        value: lit.to_owned(),
+                        span: Span::new(token.span.start, token.span.end),
+                    }));
+                }
+                (
+                    UVLexerTokens::Literal(lit) | UVLexerTokens::RawString(lit),
+                    UVParseState::ClosingTagName,
+                ) => {
+                    parse_state = UVParseState::ClosingAngleBracketClosingTag;
+                    closing_tag_name = lit.to_owned();
+                    closing_tag_span = token.span;
+                }
+
+                // New tag inside of the unfinished tag header or closing tag.
+                // Consider the header finished and parse the tag as a child
+                (
+                    UVLexerTokens::OpeningAngleBracket
+                    | UVLexerTokens::OpeningAngleBracketSlash
+                    | UVLexerTokens::InstructionStart,
+                    UVParseState::TagName
+                    | UVParseState::Attributes
+                    | UVParseState::AttributeKey
+                    | UVParseState::ClosingTagName
+                    | UVParseState::ClosingAngleBracketClosingTag,
+                ) => {
+                    self.report_unexpected(&token);
+                    tag.children.push(UVParseBody::Error(token.span));
+                    self.iter.step_back();
+                    parse_state = UVParseState::TagBody;
+                }
+
+                // Any other token is skipped
+                _ => {
+                    self.report_unexpected(&token);
+                    if !matches!(parse_state, UVParseState::Unknown) {
+                        tag.children.push(UVParseBody::Error(token.span));
+                    }
                 }
             }
         }
 
-        let span = match self.iter.vec.last() {
-            Some(token) => Span::new(token.span.end - 3, token.span.end),
-            None => Span::default(),
-        };
-        return Err(SpannedError::new("Unexpected EOF", span));
+        let span = self
+            .iter
+            .vec
+            .last()
+            .map_or(Span::default(), |token| token.span);
+
+        // Only the innermost unclosed tag reports EOF
+        if !self.reached_eof || matches!(parse_state, UVParseState::Unknown) {
+            self.reached_eof = true;
+            self.report(ErrorCode::UnexpectedEof, "Unexpected EOF", span);
+        }
+
+        if matches!(parse_state, UVParseState::Unknown) {
+            return None;
+        }
+        tag.children.push(UVParseBody::Error(span));
+        tag.span.end = span.end;
+        Some(tag)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        check,
+        errors::codes::ErrorCode,
         lexer::Lexer,
+        parse,
         tokens_parser::{
             TokenParser,
-            types::{UVParseBody, UVParseNode},
+            types::{UVAttribute, UVParseBody, UVParseNode},
         },
-        types::{Span, Spanned},
+        types::{Positional, SourceFile, Span, Spanned},
     };
 
     fn get_nodes(code: &str) -> UVParseNode {
@@ -162,11 +456,13 @@ mod tests {
                     name: "inner".to_owned(),
                     children: vec![],
                     self_closing: true,
-                    extra_param: String::new(),
+                    attributes: Vec::new(),
+                    instructions: Vec::new(),
                     span: Span::new(6, 14)
                 }))],
                 self_closing: false,
-                extra_param: String::new(),
+                attributes: Vec::new(),
+                instructions: Vec::new(),
                 span: Span::new(0, 21)
             }
         )
@@ -183,7 +479,8 @@ mod tests {
                     span: Span::new(6, 13)
                 })],
                 self_closing: false,
-                extra_param: String::new(),
+                attributes: Vec::new(),
+                instructions: Vec::new(),
                 span: Span::new(0, 20)
             }
         )
@@ -194,4 +491,236 @@ mod tests {
     fn unexpected_token() {
         get_nodes("<main>literal?</main>");
     }
+
+    #[test]
+    fn incomplete_input() {
+        let mut parser = TokenParser::new(Lexer::new("<a/><b><c>".to_owned()).parse());
+        assert!(parser.parse_all().is_err());
+        assert!(parser.reached_eof());
+
+        let mut parser = TokenParser::new(Lexer::new("<a></b>".to_owned()).parse());
+        assert_eq!(parser.parse_all().unwrap_err().len(), 2);
+
+        let mut parser = TokenParser::new(Lexer::new("<a/> <b></b>".to_owned()).parse());
+        assert_eq!(parser.parse_all().unwrap().len(), 2);
+    }
+
+    fn get_errors(code: &str) -> (UVParseNode, Vec<String>) {
+        let (node, errors) =
+            TokenParser::new(Lexer::new(code.to_owned()).parse()).parse_with_recovery();
+        let messages = errors.iter().map(|err| err.message().to_owned()).collect();
+        (node.unwrap(), messages)
+    }
+
+    #[test]
+    fn recovery() {
+        let (node, errors) = get_errors("<main><a>?</a><b></c><d/></b><e><f/></main>");
+        assert_eq!(
+            errors,
+            [
+                "Unexpected token: `?`",
+                "Unexpected closing tag `c`. Expected `b`",
+                "Unexpected closing tag `main`. Expected `e`"
+            ]
+        );
+
+        // All children are kept, damaged ones are marked
+        let names: Vec<&str> = node
+            .get_all_tags()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(names, ["a", "b", "e"]);
+        assert!(node.get_child_by_name("a").unwrap().has_errors());
+        assert!(
+            node.get_child_by_name("b")
+                .unwrap()
+                .get_child_by_name("d")
+                .is_some()
+        );
+        assert!(
+            !node
+                .get_child_by_name("e")
+                .unwrap()
+                .get_tag_at(0)
+                .unwrap()
+                .has_errors()
+        );
+        assert_eq!(node.span, Span::new(0, 43));
+    }
+
+    #[test]
+    fn recovery_resynchronize() {
+        // Mismatch is reported once, even if several tags are left unclosed
+        let (node, errors) = get_errors("<program><main><a><b></program>");
+        assert_eq!(errors, ["Unexpected closing tag `program`. Expected `b`"]);
+        assert_eq!(node.span, Span::new(0, 31));
+
+        let (node, errors) = get_errors("<program><main><a></program>");
+        assert_eq!(errors, ["Unexpected closing tag `program`. Expected `a`"]);
+        assert!(node.has_errors());
+    }
+
+    #[test]
+    fn attributes() {
+        let node = get_nodes(r#"<let name="x" const><value /></let>"#);
+        assert_eq!(
+            node.attributes,
+            [
+                UVAttribute {
+                    key: Spanned::new("name".to_owned(), Span::new(5, 9)),
+                    value: Some(Spanned::new("x".to_owned(), Span::new(11, 12))),
+                    span: Span::new(5, 13),
+                },
+                UVAttribute {
+                    key: Spanned::new("const".to_owned(), Span::new(14, 19)),
+                    value: None,
+                    span: Span::new(14, 19),
+                },
+            ]
+        );
+        assert_eq!(node.get_attribute_value("name").unwrap().value, "x");
+        assert!(node.get_attribute_value("const").is_none());
+        assert!(node.has_attribute("const"));
+        assert_eq!(node.children_len(), 1);
+    }
+
+    #[test]
+    fn invalid_attributes() {
+        let mut parser =
+            TokenParser::new(Lexer::new(r#"<a b b c=d e="f">g</a>"#.to_owned()).parse());
+        let (node, errors) = parser.parse_with_recovery();
+        let messages: Vec<&str> = errors.iter().map(|e| e.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Duplicate attribute `b`",
+                "Expected quoted value of `c` attribute"
+            ]
+        );
+
+        // Header is still parsed after errors
+        let node = node.unwrap();
+        assert_eq!(node.attributes.len(), 5);
+        assert_eq!(node.get_attribute_value("e").unwrap().value, "f");
+        assert!(node.has_errors());
+    }
+
+    #[test]
+    fn signs_in_names() {
+        let (_, errors) = get_errors("<a-b><x+1 /><c d-e=\"1\" /></a-b>");
+        assert_eq!(
+            errors,
+            [
+                "Tag name `a-b` cannot contain `-`",
+                "Tag name `x+1` cannot contain `+`",
+                "Attribute key `d-e` cannot contain `-`"
+            ]
+        );
+
+        // Signs are allowed in body literals and attribute values
+        let (_, errors) = get_errors("<a><int>-5</int><float value=\"1e+3\" /></a>");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn instructions() {
+        let node = get_nodes(r#"<?uv version="1.0"?><?tool?><main></main>"#);
+        let instruction = node.get_instruction("uv").unwrap();
+        assert_eq!(instruction.target.span, Span::new(2, 4));
+        assert_eq!(instruction.span, Span::new(0, 20));
+        assert_eq!(
+            instruction.get_attribute_value("version"),
+            Some(&Spanned::new("1.0".to_owned(), Span::new(14, 17)))
+        );
+        assert!(node.get_instruction("tool").is_some());
+        assert_eq!(node.name, "main");
+    }
+
+    #[test]
+    fn misplaced_instructions() {
+        let mut parser =
+            TokenParser::new(Lexer::new(r#"<?uv version="1"><a><?uv?></a>"#.to_owned()).parse());
+        let (node, errors) = parser.parse_with_recovery();
+        let messages: Vec<&str> = errors.iter().map(|e| e.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Processing instruction must be closed with `?>`",
+                "Processing instructions are allowed only before the root tag"
+            ]
+        );
+
+        let node = node.unwrap();
+        assert_eq!(node.name, "a");
+        assert!(node.get_instruction("uv").is_some());
+        assert!(node.has_errors());
+    }
+
+    #[test]
+    fn cdata() {
+        // Raw strings keep sections verbatim
+        let node = get_nodes("<str-a><![CDATA[<b>]]></str-a>");
+        assert_eq!(node.get_inner_literal().unwrap().value, "<![CDATA[<b>]]>");
+
+        let node = get_nodes("<int><![CDATA[5]]></int>");
+        assert_eq!(
+            node.get_inner_literal(),
+            Some(&Spanned::new("5".to_owned(), Span::new(14, 15)))
+        );
+    }
+
+    #[test]
+    fn recovery_eof() {
+        let (node, errors) = get_errors("<main><a><b>");
+        assert_eq!(errors, ["Unexpected EOF"]);
+        assert_eq!(node.get_tag_at(0).unwrap().name, "a");
+    }
+
+    #[test]
+    fn truncated_input() {
+        for (code, span) in [
+            ("<", Span::new(0, 1)),
+            ("<a", Span::new(1, 2)),
+            ("<a b", Span::new(3, 4)),
+            ("<main><a>", Span::new(8, 9)),
+        ] {
+            let errors = TokenParser::new(Lexer::new(code.to_owned()).parse())
+                .parse_all()
+                .unwrap_err();
+            let eof = errors.last().unwrap();
+            assert_eq!(eof.message(), "Unexpected EOF", "{code}");
+            assert_eq!(eof.get_span(), span, "{code}");
+        }
+    }
+
+    #[test]
+    fn processing_instructions() {
+        let source =
+            SourceFile::from_string("<?uv version=\"1.0\"?><program><main></main></program>");
+
+        let node = parse(&source).unwrap();
+        let version = node
+            .get_instruction("uv")
+            .and_then(|instruction| instruction.get_attribute_value("version"))
+            .unwrap();
+        assert_eq!(version.value, "1.0");
+        assert!(check(&source).unwrap().warnings.is_empty());
+
+        let source = SourceFile::from_string(
+            "<?xml version=\"1.0\"?><?uv version=\"99\"?><program><main></main></program>",
+        );
+
+        let warnings = check(&source).unwrap().warnings;
+        let messages: Vec<&str> = warnings.iter().map(|warning| warning.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unknown processing instruction `xml`",
+                "Unsupported language version `99`"
+            ]
+        );
+        assert!(warnings.iter().all(|warning| !warning.is_error()));
+        assert_eq!(warnings[0].code(), Some(ErrorCode::UnknownInstruction));
+    }
 }
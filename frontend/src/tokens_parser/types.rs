@@ -8,8 +8,12 @@ pub struct UVParseNode {
     /// Node is self-closing `<name />`
     pub self_closing: bool,
 
-    /// Node extra param `<name extra_param>...</ node>`
-    pub extra_param: String,
+    /// Attributes of the header `<name key="value" flag>` in the source order
+    pub attributes: Vec<UVAttribute>,
+
+    /// Processing instructions `<?target ...?>` before the tag.
+    /// Instructions are allowed only before the root tag
+    pub instructions: Vec<UVInstruction>,
 
     pub span: Span,
 }
@@ -28,6 +32,28 @@ impl UVParseNode {
         })
     }
 
+    /// Get attribute by key
+    pub fn get_attribute(&self, key: &str) -> Option<&UVAttribute> {
+        self.attributes.iter().find(|attr| attr.key.value == key)
+    }
+
+    /// Get value of `key="value"` attribute. Bare flags have no value
+    pub fn get_attribute_value(&self, key: &str) -> Option<&Spanned<String>> {
+        self.get_attribute(key).and_then(|attr| attr.value.as_ref())
+    }
+
+    /// Check if node has attribute with provided key, with or without value
+    pub fn has_attribute(&self, key: &str) -> bool {
+        self.get_attribute(key).is_some()
+    }
+
+    /// Get processing instruction by target, such as `uv` for `<?uv version="1"?>`
+    pub fn get_instruction(&self, target: &str) -> Option<&UVInstruction> {
+        self.instructions
+            .iter()
+            .find(|instruction| instruction.target.value == target)
+    }
+
     /// Get first inner literal
     pub fn get_inner_literal(&self) -> Option<&Spanned<String>> {
         self.children.iter().find_map(|ch| match ch {
@@ -52,18 +78,27 @@ impl UVParseNode {
         }
     }
 
-    /// Check if all children is literals
+    /// Check if all children is literals. Error markers are ignored
     pub fn all_literals(&self) -> bool {
         self.children
             .iter()
-            .all(|ch| matches!(ch, UVParseBody::String(_)))
+            .all(|ch| matches!(ch, UVParseBody::String(_) | UVParseBody::Error(_)))
     }
 
-    /// Check if all children is tags
+    /// Check if all children is tags. Error markers are ignored
     pub fn all_tags(&self) -> bool {
         self.children
             .iter()
-            .all(|ch| matches!(ch, UVParseBody::Tag(_)))
+            .all(|ch| matches!(ch, UVParseBody::Tag(_) | UVParseBody::Error(_)))
+    }
+
+    /// Check if node or any of nested nodes was damaged by parse error
+    pub fn has_errors(&self) -> bool {
+        self.children.iter().any(|ch| match ch {
+            UVParseBody::Tag(node) => node.has_errors(),
+            UVParseBody::String(_) => false,
+            UVParseBody::Error(_) => true,
+        })
     }
 
     /// Search extra children, that not included in white list
@@ -73,7 +108,8 @@ impl UVParseNode {
             .iter()
             .filter(|ch| match ch {
                 UVParseBody::Tag(node) => !white_list_strings.contains(&node.name),
-                _ => true,
+                UVParseBody::String(_) => true,
+                UVParseBody::Error(_) => false,
             })
             .cloned()
             .collect()
@@ -85,7 +121,7 @@ impl UVParseNode {
             .iter()
             .filter_map(|ch| match ch {
                 UVParseBody::Tag(node) => Some(node.as_ref()),
-                UVParseBody::String(_) => None,
+                UVParseBody::String(_) | UVParseBody::Error(_) => None,
             })
             .collect()
     }
@@ -93,10 +129,59 @@ impl UVParseNode {
 
 // -------------------------------------
 
+/// Tag attribute `key="value"` or bare flag `key`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UVAttribute {
+    pub key: Spanned<String>,
+
+    /// Value without quotes. Span covers only the content of quotes
+    pub value: Option<Spanned<String>>,
+
+    /// Span of the whole attribute
+    pub span: Span,
+}
+
+impl Positional for UVAttribute {
+    fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+// -------------------------------------
+
+/// Processing instruction `<?target key="value"?>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UVInstruction {
+    pub target: Spanned<String>,
+    pub attributes: Vec<UVAttribute>,
+    pub span: Span,
+}
+
+impl UVInstruction {
+    /// Get value of `key="value"` attribute of the instruction
+    pub fn get_attribute_value(&self, key: &str) -> Option<&Spanned<String>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.key.value == key)
+            .and_then(|attr| attr.value.as_ref())
+    }
+}
+
+impl Positional for UVInstruction {
+    fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+// -------------------------------------
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum UVParseBody {
     String(Spanned<String>),
     Tag(Box<UVParseNode>),
+
+    /// Marker of the place, where parse error was found
+    Error(Span),
 }
 
 impl Positional for UVParseBody {
@@ -104,6 +189,7 @@ impl Positional for UVParseBody {
         match self {
             UVParseBody::String(type_with_span) => type_with_span.span,
             UVParseBody::Tag(uvparse_node) => uvparse_node.span,
+            UVParseBody::Error(span) => *span,
         }
     }
 }
@@ -113,8 +199,15 @@ pub enum UVParseState {
     Unknown,
     TagName,
     TagBody,
-    ExtraParam,
-    ClosingAngleBracketOpeningTag,
+
+    /// Inside of the header after tag name or complete attribute
+    Attributes,
+
+    /// After attribute key, where `=` can follow
+    AttributeKey,
+
+    /// After `=`, where quoted value must follow
+    AttributeValue,
     ClosingAngleBracketClosingTag,
     ClosingTagName,
 }
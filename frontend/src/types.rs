@@ -20,9 +20,20 @@ impl<'a> SourceFile<'a> {
     Returns `Err` when provided file not found or cannot be read
     */
     pub fn load(path: &'a Path) -> Result<Self> {
-        let code: String = fs::read_to_string(path)?;
-        Ok(Self {
-            path: path,
+        let code: String =
+            fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        Ok(Self::new(path, code))
+    }
+
+    /// Create source file from code, that does not belong to any file
+    pub fn from_string(code: impl Into<String>) -> SourceFile<'static> {
+        SourceFile::new(Path::new("<string>"), code.into())
+    }
+
+    /// Create source file from already loaded code
+    pub fn new(path: &'a Path, code: String) -> Self {
+        Self {
+            path,
             code: code.clone(),
             char_to_byte: code.char_indices().map(|(i, _)| i).collect(),
             line_starts: std::iter::once(0)
@@ -30,10 +41,10 @@ impl<'a> SourceFile<'a> {
                     code.chars()
                         .enumerate()
                         .filter(|(_, c)| *c == '\n')
-                        .map(|(i, _)| i),
+                        .map(|(i, _)| i + 1),
                 )
                 .collect(),
-        })
+        }
     }
 
     /// Get line and column of provided Span
@@ -44,6 +55,14 @@ impl<'a> SourceFile<'a> {
         (line, column)
     }
 
+    /// Convert char index to byte offset in the code
+    pub fn byte_offset(&self, char_index: usize) -> usize {
+        self.char_to_byte
+            .get(char_index)
+            .copied()
+            .unwrap_or(self.code.len())
+    }
+
     /// Search line No by provided Span start
     fn get_line(&self, target: usize) -> Option<usize> {
         if self.line_starts.is_empty() || target < self.line_starts[0] {
@@ -65,24 +84,21 @@ impl<'a> SourceFile<'a> {
     /// Get full line by provided line No
     pub fn get_line_content(&'a self, line: usize) -> Result<&'a str> {
         let line_index_start = self.line_starts.get(line).context("")?;
-        let code_len = self.code.len();
-        let line_index_end = self.line_starts.get(line + 1).unwrap_or(&code_len);
-
-        // Convert char indexes to a bytes
-        let line_start_byte = self.char_to_byte.get(*line_index_start).context("")?;
-        let line_end_byte = self.char_to_byte.get(*line_index_end).context("")?;
+        let chars_len = self.char_to_byte.len();
+        let line_index_end = self.line_starts.get(line + 1).unwrap_or(&chars_len);
 
+        // The last line ends with the end of code
         let line_content = self
             .code
-            .get(*line_start_byte..*line_end_byte)
+            .get(self.byte_offset(*line_index_start)..self.byte_offset(*line_index_end))
             .context("")?
-            .trim_end_matches("\n");
+            .trim_end_matches(['\r', '\n']);
 
         Ok(line_content)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, Default)]
 
 /// Span displays the portion of the source code that a token or AST node occupies
 pub struct Span {
@@ -97,12 +113,6 @@ impl Span {
     }
 }
 
-impl Default for Span {
-    fn default() -> Self {
-        Self { start: 0, end: 0 }
-    }
-}
-
 pub trait Positional {
     /// Get associated Span
     fn get_span(&self) -> Span;
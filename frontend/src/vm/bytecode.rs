@@ -0,0 +1,572 @@
+//! Binary format of the compiled program.
+//!
+//! All numbers are little-endian, indexes and sizes are stored as `u32`:
+//!
+//! ```text
+//! magic      b"UVBC"
+//! version    u16
+//! strings    u32 count, then (u32 length, utf-8 bytes) for each string
+//! constants  u32 count, then (u8 tag, payload) for each constant
+//! entry      u32 index of the entry function
+//! functions  u32 count, then for each function:
+//!            u32 name (string index), u32 arity, u32 locals,
+//!            u32 instruction count, instructions (u8 opcode, operands),
+//!            span table (u32 start, u32 end) for each instruction
+//! ```
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    ast::types::{CompareOpType, MathOpType, UVValue},
+    types::Span,
+    vm::instructions::{Chunk, Instruction, Program},
+};
+
+pub const MAGIC: &[u8; 4] = b"UVBC";
+pub const VERSION: u16 = 1;
+
+impl Program {
+    /// Save program to bytecode file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("Cannot write bytecode to {}", path.display()))
+    }
+
+    /// Load program from bytecode file
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Cannot read bytecode from {}", path.display()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Encode program into bytecode
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut encoder = Encoder::default();
+
+        encoder.u32(self.constants.len())?;
+        for constant in &self.constants {
+            encoder.constant(constant)?;
+        }
+
+        encoder.u32(self.entry)?;
+        encoder.u32(self.functions.len())?;
+        for chunk in &self.functions {
+            encoder.chunk(chunk)?;
+        }
+
+        encoder.finish()
+    }
+
+    /// Decode program from bytecode
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder {
+            bytes,
+            position: 0,
+            strings: Vec::new(),
+        };
+
+        if decoder.take(MAGIC.len())? != MAGIC {
+            bail!("Invalid bytecode: missing `UVBC` header");
+        }
+
+        let version = u16::from_le_bytes(decoder.array()?);
+        if version != VERSION {
+            bail!("Unsupported bytecode version {version}, expected {VERSION}");
+        }
+
+        let strings = (0..decoder.u32()?)
+            .map(|_| {
+                let len = decoder.u32()?;
+                Ok(String::from_utf8(decoder.take(len)?.to_vec())?)
+            })
+            .collect::<Result<Vec<String>>>()?;
+        decoder.strings = strings;
+
+        let constants = (0..decoder.u32()?)
+            .map(|_| decoder.constant())
+            .collect::<Result<Vec<UVValue>>>()?;
+
+        let entry = decoder.u32()?;
+        let functions = (0..decoder.u32()?)
+            .map(|_| decoder.chunk())
+            .collect::<Result<Vec<Chunk>>>()?;
+
+        if decoder.position != bytes.len() {
+            bail!("Invalid bytecode: unexpected data after the last function");
+        }
+
+        let program = Program {
+            constants,
+            functions,
+            entry,
+        };
+        validate(&program)?;
+        Ok(program)
+    }
+}
+
+mod opcode {
+    pub const CONST: u8 = 0;
+    pub const POP: u8 = 1;
+    pub const LOAD: u8 = 2;
+    pub const STORE: u8 = 3;
+    pub const MATH: u8 = 4;
+    pub const COMPARE: u8 = 5;
+    pub const NOT: u8 = 6;
+    pub const CHECK_BOUND: u8 = 7;
+    pub const JUMP: u8 = 8;
+    pub const JUMP_IF: u8 = 9;
+    pub const FOR_INIT: u8 = 10;
+    pub const FOR_NEXT: u8 = 11;
+    pub const FOR_STEP: u8 = 12;
+    pub const CALL: u8 = 13;
+    pub const RETURN: u8 = 14;
+    pub const PRINT: u8 = 15;
+}
+
+mod tag {
+    pub const NULL: u8 = 0;
+    pub const INT: u8 = 1;
+    pub const FLOAT: u8 = 2;
+    pub const BOOL: u8 = 3;
+    pub const STRING: u8 = 4;
+}
+
+const MATH_OPS: [MathOpType; 5] = [
+    MathOpType::Sum,
+    MathOpType::Sub,
+    MathOpType::Mul,
+    MathOpType::Div,
+    MathOpType::Mod,
+];
+
+const COMPARE_OPS: [CompareOpType; 6] = [
+    CompareOpType::Equality,
+    CompareOpType::NotEquality,
+    CompareOpType::Greater,
+    CompareOpType::GreaterEquals,
+    CompareOpType::Less,
+    CompareOpType::LessEquals,
+];
+
+/// Writer of the program body. String table is collected on the go
+/// and placed before the body in `finish`
+#[derive(Default)]
+struct Encoder {
+    body: Vec<u8>,
+    strings: Vec<String>,
+    string_indexes: HashMap<String, usize>,
+}
+
+impl Encoder {
+    fn finish(self) -> Result<Vec<u8>> {
+        let mut header = Encoder::default();
+        header.body.extend(MAGIC);
+        header.body.extend(VERSION.to_le_bytes());
+
+        header.u32(self.strings.len())?;
+        for string in &self.strings {
+            header.u32(string.len())?;
+            header.body.extend(string.as_bytes());
+        }
+
+        header.body.extend(self.body);
+        Ok(header.body)
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.body.push(value);
+    }
+
+    /// Operations are stored as their index in the decoder table
+    fn operation<T: PartialEq>(&mut self, table: &[T], op: &T) {
+        let index = table
+            .iter()
+            .position(|item| item == op)
+            .expect("Operation table is complete");
+        self.u8(index as u8);
+    }
+
+    fn u32(&mut self, value: usize) -> Result<()> {
+        let value = u32::try_from(value).context("Program is too large for bytecode")?;
+        self.body.extend(value.to_le_bytes());
+        Ok(())
+    }
+
+    fn string(&mut self, value: &str) -> Result<()> {
+        let index = match self.string_indexes.get(value) {
+            Some(index) => *index,
+            None => {
+                self.strings.push(value.to_owned());
+                self.string_indexes
+                    .insert(value.to_owned(), self.strings.len() - 1);
+                self.strings.len() - 1
+            }
+        };
+        self.u32(index)
+    }
+
+    fn constant(&mut self, value: &UVValue) -> Result<()> {
+        match value {
+            UVValue::Null => self.u8(tag::NULL),
+            UVValue::Int(i) => {
+                self.u8(tag::INT);
+                self.body.extend(i.to_le_bytes());
+            }
+            UVValue::Float(f) => {
+                self.u8(tag::FLOAT);
+                self.body.extend(f.to_bits().to_le_bytes());
+            }
+            UVValue::Boolean(b) => {
+                self.u8(tag::BOOL);
+                self.u8(*b as u8);
+            }
+            UVValue::String(s) => {
+                self.u8(tag::STRING);
+                self.string(s)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.string(&chunk.name)?;
+        self.u32(chunk.arity)?;
+        self.u32(chunk.locals)?;
+
+        self.u32(chunk.code.len())?;
+        for instruction in &chunk.code {
+            self.instruction(instruction)?;
+        }
+        for span in &chunk.spans {
+            self.u32(span.start)?;
+            self.u32(span.end)?;
+        }
+        Ok(())
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) -> Result<()> {
+        match instruction {
+            Instruction::Const(index) => {
+                self.u8(opcode::CONST);
+                self.u32(*index)?;
+            }
+            Instruction::Pop => self.u8(opcode::POP),
+            Instruction::Load(slot) => {
+                self.u8(opcode::LOAD);
+                self.u32(*slot)?;
+            }
+            Instruction::Store(slot) => {
+                self.u8(opcode::STORE);
+                self.u32(*slot)?;
+            }
+            Instruction::Math(op) => {
+                self.u8(opcode::MATH);
+                self.operation(&MATH_OPS, op);
+            }
+            Instruction::Compare(op) => {
+                self.u8(opcode::COMPARE);
+                self.operation(&COMPARE_OPS, op);
+            }
+            Instruction::Not => self.u8(opcode::NOT),
+            Instruction::CheckBound => self.u8(opcode::CHECK_BOUND),
+            Instruction::Jump(target) => {
+                self.u8(opcode::JUMP);
+                self.u32(*target)?;
+            }
+            Instruction::JumpIf(expected, target) => {
+                self.u8(opcode::JUMP_IF);
+                self.u8(*expected as u8);
+                self.u32(*target)?;
+            }
+            Instruction::ForInit(slot) => {
+                self.u8(opcode::FOR_INIT);
+                self.u32(*slot)?;
+            }
+            Instruction::ForNext(slot, exit) => {
+                self.u8(opcode::FOR_NEXT);
+                self.u32(*slot)?;
+                self.u32(*exit)?;
+            }
+            Instruction::ForStep(slot) => {
+                self.u8(opcode::FOR_STEP);
+                self.u32(*slot)?;
+            }
+            Instruction::Call(index) => {
+                self.u8(opcode::CALL);
+                self.u32(*index)?;
+            }
+            Instruction::Return => self.u8(opcode::RETURN),
+            Instruction::Print(count) => {
+                self.u8(opcode::PRINT);
+                self.u32(*count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    strings: Vec<String>,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + len)
+            .context("Invalid bytecode: unexpected end of data")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => bail!("Invalid bytecode: `{other}` is not a boolean"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let index = self.u32()?;
+        self.strings
+            .get(index)
+            .cloned()
+            .with_context(|| format!("Invalid bytecode: unknown string #{index}"))
+    }
+
+    fn constant(&mut self) -> Result<UVValue> {
+        Ok(match self.u8()? {
+            tag::NULL => UVValue::Null,
+            tag::INT => UVValue::Int(i64::from_le_bytes(self.array()?)),
+            tag::FLOAT => UVValue::Float(f64::from_bits(u64::from_le_bytes(self.array()?))),
+            tag::BOOL => UVValue::Boolean(self.bool()?),
+            tag::STRING => UVValue::String(self.string()?),
+            other => bail!("Invalid bytecode: unknown constant tag `{other}`"),
+        })
+    }
+
+    fn chunk(&mut self) -> Result<Chunk> {
+        let name = self.string()?;
+        let arity = self.u32()?;
+        let locals = self.u32()?;
+
+        let len = self.u32()?;
+        let code = (0..len)
+            .map(|_| self.instruction())
+            .collect::<Result<Vec<Instruction>>>()?;
+        let spans = (0..len)
+            .map(|_| Ok(Span::new(self.u32()?, self.u32()?)))
+            .collect::<Result<Vec<Span>>>()?;
+
+        Ok(Chunk {
+            name,
+            arity,
+            locals,
+            code,
+            spans,
+        })
+    }
+
+    fn instruction(&mut self) -> Result<Instruction> {
+        Ok(match self.u8()? {
+            opcode::CONST => Instruction::Const(self.u32()?),
+            opcode::POP => Instruction::Pop,
+            opcode::LOAD => Instruction::Load(self.u32()?),
+            opcode::STORE => Instruction::Store(self.u32()?),
+            opcode::MATH => {
+                let op = self.u8()?;
+                Instruction::Math(
+                    MATH_OPS.get(op as usize).cloned().with_context(|| {
+                        format!("Invalid bytecode: unknown math operation `{op}`")
+                    })?,
+                )
+            }
+            opcode::COMPARE => {
+                let op = self.u8()?;
+                Instruction::Compare(COMPARE_OPS.get(op as usize).cloned().with_context(|| {
+                    format!("Invalid bytecode: unknown compare operation `{op}`")
+                })?)
+            }
+            opcode::NOT => Instruction::Not,
+            opcode::CHECK_BOUND => Instruction::CheckBound,
+            opcode::JUMP => Instruction::Jump(self.u32()?),
+            opcode::JUMP_IF => Instruction::JumpIf(self.bool()?, self.u32()?),
+            opcode::FOR_INIT => Instruction::ForInit(self.u32()?),
+            opcode::FOR_NEXT => Instruction::ForNext(self.u32()?, self.u32()?),
+            opcode::FOR_STEP => Instruction::ForStep(self.u32()?),
+            opcode::CALL => Instruction::Call(self.u32()?),
+            opcode::RETURN => Instruction::Return,
+            opcode::PRINT => Instruction::Print(self.u32()?),
+            other => bail!("Invalid bytecode: unknown opcode `{other}`"),
+        })
+    }
+}
+
+/// Check that all indexes of the loaded program are in bounds,
+/// so the machine never reads outside of constants, functions or slots.
+/// Stack depth depends on the control flow and is checked by the machine
+fn validate(program: &Program) -> Result<()> {
+    let entry = program
+        .functions
+        .get(program.entry)
+        .context("Invalid bytecode: unknown entry function")?;
+    if entry.arity != 0 {
+        bail!("Invalid bytecode: entry function cannot have parameters");
+    }
+
+    for chunk in &program.functions {
+        if chunk.arity > chunk.locals {
+            bail!(
+                "Invalid bytecode: `{}` has more parameters than slots",
+                chunk.name
+            );
+        }
+
+        for instruction in &chunk.code {
+            let valid = match instruction {
+                Instruction::Const(index) => *index < program.constants.len(),
+                Instruction::Load(slot) | Instruction::Store(slot) => *slot < chunk.locals,
+                Instruction::Jump(target) | Instruction::JumpIf(_, target) => {
+                    *target < chunk.code.len()
+                }
+                Instruction::ForInit(slot) | Instruction::ForStep(slot) => slot + 3 <= chunk.locals,
+                Instruction::ForNext(slot, exit) => {
+                    slot + 3 <= chunk.locals && *exit < chunk.code.len()
+                }
+                Instruction::Call(index) => *index < program.functions.len(),
+                _ => true,
+            };
+
+            if !valid {
+                bail!(
+                    "Invalid bytecode: `{instruction:?}` is out of bounds in `{}`",
+                    chunk.name
+                );
+            }
+        }
+
+        if !matches!(chunk.code.last(), Some(Instruction::Return)) {
+            bail!(
+                "Invalid bytecode: `{}` does not end with return",
+                chunk.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::types::UVValue,
+        interpreter::tests::{CASES, parse},
+        types::Span,
+        vm::{
+            compile,
+            instructions::{Chunk, Instruction, Program},
+            machine::Machine,
+        },
+    };
+
+    fn chunk(arity: usize, locals: usize, code: &[Instruction]) -> Chunk {
+        let mut chunk = Chunk::new("f", arity);
+        chunk.locals = locals;
+        for instruction in code {
+            chunk.emit(instruction.clone(), Span::new(0, 1));
+        }
+        chunk
+    }
+
+    #[test]
+    fn roundtrip() {
+        for (main, expected) in CASES {
+            let bytes = compile(&parse(main)).unwrap().to_bytes().unwrap();
+            let program = Program::from_bytes(&bytes).unwrap();
+            assert_eq!(program.to_bytes().unwrap(), bytes);
+
+            let mut machine = Machine::new(Vec::new());
+            machine.run(&program).unwrap();
+            assert_eq!(String::from_utf8(machine.into_output()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn invalid_header() {
+        let err = Program::from_bytes(b"UVB").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid bytecode: unexpected end of data");
+
+        let err = Program::from_bytes(b"ABCD\x01\x00").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid bytecode: missing `UVBC` header");
+
+        let err = Program::from_bytes(b"UVBC\x02\x00").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unsupported bytecode version 2, expected 1"
+        );
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut bytes = compile(&parse("<println><int>1</int></println>"))
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+
+        // Last instruction before the span table is `Return`, replace it with `Call(7)`
+        let code_end = bytes.len() - 4 * 8 - 1;
+        bytes.splice(code_end..code_end + 1, [13, 7, 0, 0, 0]);
+
+        let err = Program::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid bytecode: `Call(7)` is out of bounds in `<main>`"
+        );
+    }
+
+    #[test]
+    fn stack_underflow() {
+        use Instruction::*;
+
+        let callee = chunk(2, 2, &[Load(0), Return]);
+        let programs = [
+            // Print more values than pushed
+            chunk(0, 0, &[Const(0), Print(5), Const(0), Return]),
+            // Pop value of the local slot, then load it
+            chunk(0, 1, &[Pop, Load(0), Return]),
+            chunk(0, 1, &[Store(0), Const(0), Return]),
+            // Call function without arguments on the stack
+            chunk(0, 0, &[Const(0), Call(1), Return]),
+        ];
+
+        for main in programs {
+            let program = Program {
+                constants: vec![UVValue::Int(1)],
+                functions: vec![main, callee.clone()],
+                entry: 0,
+            };
+            let program = Program::from_bytes(&program.to_bytes().unwrap()).unwrap();
+
+            let err = Machine::new(Vec::new()).run(&program).unwrap_err();
+            assert_eq!(err.message(), "[INTERNAL ERROR] Stack underflow");
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::types::{
+        ASTBlockType, ConditionalOp, ForLoop, FunctionCall, FunctionDefinition, LogicalOp,
+        LogicalOpType, MathOp, UVType, UVValue,
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    interpreter::operations::math_identity,
+    types::{Positional, Span},
+    vm::instructions::{Chunk, Instruction, Program, Slot},
+};
+
+/// Function known to the compiler before its body is compiled
+struct Signature {
+    index: usize,
+    params: Vec<String>,
+}
+
+/// Compiler of the checked AST into the stack machine instructions.
+///
+/// Every compiled expression leaves exactly one value on the stack,
+/// statements without a result leave `null`
+pub struct Compiler {
+    constants: Vec<UVValue>,
+    signatures: HashMap<String, Signature>,
+
+    /// Chunk being compiled
+    chunk: Chunk,
+
+    /// Lexical scopes of the current chunk
+    scopes: Vec<HashMap<String, Slot>>,
+
+    in_function: bool,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            constants: Vec::new(),
+            signatures: HashMap::new(),
+            chunk: Chunk::new("", 0),
+            scopes: Vec::new(),
+            in_function: false,
+        }
+    }
+
+    /// Compile <program>. Top-level code of <head> and <main> goes to the entry chunk
+    pub fn compile_program(mut self, program: &ASTBlockType) -> Result<Program, SpannedError> {
+        let ASTBlockType::Program(program) = program else {
+            return Err(SpannedError::new(
+                "[INTERNAL ERROR] Compiler expects <program> block",
+                program.get_span(),
+            ));
+        };
+
+        let mut root = Vec::new();
+        for block in program.head.iter().chain([&program.main]) {
+            if let ASTBlockType::HeadBlock(statements) | ASTBlockType::MainBlock(statements) = block
+            {
+                root.extend(statements);
+            }
+        }
+
+        // Functions are declared before compilation, so they can be called before definition
+        let definitions: Vec<&FunctionDefinition> = root
+            .iter()
+            .filter_map(|statement| match statement {
+                ASTBlockType::FunctionDefinition(function) => Some(function.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        for (index, function) in definitions.iter().enumerate() {
+            self.signatures.insert(
+                function.name.value.clone(),
+                Signature {
+                    index,
+                    params: function
+                        .params
+                        .iter()
+                        .map(|p| p.name.value.clone())
+                        .collect(),
+                },
+            );
+        }
+
+        let mut functions = Vec::new();
+        for function in definitions {
+            functions.push(self.compile_function(function)?);
+        }
+
+        self.begin_chunk(Chunk::new("<main>", 0), false);
+        if root.is_empty() {
+            self.emit_const(UVValue::Null, program.span);
+        }
+        for (i, statement) in root.iter().enumerate() {
+            if i > 0 {
+                self.chunk.emit(Instruction::Pop, statement.get_span());
+            }
+            self.compile(statement)?;
+        }
+        self.chunk.emit(Instruction::Return, program.span);
+
+        functions.push(std::mem::replace(&mut self.chunk, Chunk::new("", 0)));
+        Ok(Program {
+            constants: self.constants,
+            entry: functions.len() - 1,
+            functions,
+        })
+    }
+
+    fn begin_chunk(&mut self, chunk: Chunk, in_function: bool) {
+        self.chunk = chunk;
+        self.scopes = vec![HashMap::new()];
+        self.in_function = in_function;
+    }
+
+    fn compile_function(&mut self, function: &FunctionDefinition) -> Result<Chunk, SpannedError> {
+        self.begin_chunk(
+            Chunk::new(&function.name.value, function.params.len()),
+            true,
+        );
+
+        // Parameters occupy the first slots of the frame
+        for (slot, param) in function.params.iter().enumerate() {
+            self.scopes[0].insert(param.name.value.clone(), slot);
+        }
+
+        // Function returns `null` when execution reaches the end of its body
+        self.compile_block(&function.body.value, function.body.span)?;
+        self.chunk.emit(Instruction::Pop, function.span);
+        self.emit_const(UVValue::Null, function.span);
+        self.chunk.emit(Instruction::Return, function.span);
+
+        Ok(std::mem::replace(&mut self.chunk, Chunk::new("", 0)))
+    }
+
+    fn compile(&mut self, node: &ASTBlockType) -> Result<(), SpannedError> {
+        match node {
+            ASTBlockType::Program(_) | ASTBlockType::HeadBlock(_) | ASTBlockType::MainBlock(_) => {
+                return Err(SpannedError::new(
+                    "[INTERNAL ERROR] Unexpected root block",
+                    node.get_span(),
+                ));
+            }
+            ASTBlockType::VariableDefinition(def) => {
+                self.compile(&def.value.value)?;
+                let slot = self.declare(&def.name.value);
+                self.chunk.emit(Instruction::Store(slot), def.span);
+                self.emit_const(UVValue::Null, def.span);
+            }
+            // Functions are compiled separately in `compile_program`
+            ASTBlockType::FunctionDefinition(function) => {
+                self.emit_const(UVValue::Null, function.span);
+            }
+            ASTBlockType::FunctionCall(call) => self.compile_call(call)?,
+            ASTBlockType::VariableAssignment(assign) => {
+                self.compile(&assign.value.value)?;
+                let slot = self.lookup(&assign.name, assign.span)?;
+                self.chunk.emit(Instruction::Store(slot), assign.span);
+                self.emit_const(UVValue::Null, assign.span);
+            }
+            ASTBlockType::VariableAccess(access) => {
+                let slot = self.lookup(&access.name, access.span)?;
+                self.chunk.emit(Instruction::Load(slot), access.span);
+            }
+            ASTBlockType::ConditionalOp(op) => self.compile_conditional(op)?,
+            ASTBlockType::Return(ret) => {
+                if !self.in_function {
+                    return Err(SpannedError::new(
+                        "<return> cannot be used outside of function",
+                        ret.span,
+                    )
+                    .with_code(ErrorCode::ReturnOutsideFunction));
+                }
+
+                match &ret.value {
+                    Some(value) => self.compile(&value.value)?,
+                    None => self.emit_const(UVValue::Null, ret.span),
+                }
+                self.chunk.emit(Instruction::Return, ret.span);
+            }
+            ASTBlockType::MathOp(op) => self.compile_math(op)?,
+            ASTBlockType::LogicalOp(op) => self.compile_logical(op)?,
+            ASTBlockType::CompareOp(op) => {
+                for operand in &op.operands {
+                    self.compile(operand)?;
+                }
+                self.chunk
+                    .emit(Instruction::Compare(op.op_type.clone()), op.span);
+            }
+            ASTBlockType::ForLoop(for_loop) => self.compile_for_loop(for_loop)?,
+            ASTBlockType::WhileLoop(while_loop) => {
+                let start = self.chunk.code.len();
+                self.compile(&while_loop.condition.value)?;
+                let exit = self.chunk.emit(
+                    Instruction::JumpIf(false, 0),
+                    while_loop.condition.value.get_span(),
+                );
+
+                self.compile_block(&while_loop.body.value, while_loop.body.span)?;
+                self.chunk.emit(Instruction::Pop, while_loop.span);
+                self.chunk.emit(Instruction::Jump(start), while_loop.span);
+
+                self.patch_jump(exit);
+                self.emit_const(UVValue::Null, while_loop.span);
+            }
+            ASTBlockType::Value(value) => self.emit_const(value.value.clone(), value.span),
+            ASTBlockType::Type(t) if t.value == UVType::Null => {
+                self.emit_const(UVValue::Null, t.span)
+            }
+            ASTBlockType::Type(t) => {
+                return Err(SpannedError::new(
+                    format!("Type `{}` cannot be used as a value", t.value),
+                    t.span,
+                )
+                .with_code(ErrorCode::TypeMismatch));
+            }
+            ASTBlockType::GroupBlock(group) => self.compile_block(&group.statements, group.span)?,
+            ASTBlockType::Print(print) => {
+                for operand in &print.operands {
+                    self.compile(operand)?;
+                }
+                self.chunk
+                    .emit(Instruction::Print(print.operands.len()), print.span);
+                self.emit_const(UVValue::Null, print.span);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compile statements in a new scope. Leaves value of the last one
+    fn compile_block(
+        &mut self,
+        statements: &[ASTBlockType],
+        span: Span,
+    ) -> Result<(), SpannedError> {
+        self.scopes.push(HashMap::new());
+
+        if statements.is_empty() {
+            self.emit_const(UVValue::Null, span);
+        }
+        for (i, statement) in statements.iter().enumerate() {
+            if i > 0 {
+                self.chunk.emit(Instruction::Pop, statement.get_span());
+            }
+            self.compile(statement)?;
+        }
+
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn compile_conditional(&mut self, op: &ConditionalOp) -> Result<(), SpannedError> {
+        let mut exits = Vec::new();
+        for branch in &op.branches {
+            self.compile(&branch.condition.value)?;
+            let next = self.chunk.emit(
+                Instruction::JumpIf(false, 0),
+                branch.condition.value.get_span(),
+            );
+
+            self.compile_block(&branch.body.value, branch.body.span)?;
+            self.chunk.emit(Instruction::Pop, branch.span);
+            exits.push(self.chunk.emit(Instruction::Jump(0), branch.span));
+
+            self.patch_jump(next);
+        }
+
+        if let Some(else_body) = &op.else_body {
+            self.compile_block(&else_body.value, else_body.span)?;
+            self.chunk.emit(Instruction::Pop, else_body.span);
+        }
+
+        for exit in exits {
+            self.patch_jump(exit);
+        }
+        self.emit_const(UVValue::Null, op.span);
+        Ok(())
+    }
+
+    fn compile_math(&mut self, op: &MathOp) -> Result<(), SpannedError> {
+        let Some((first, rest)) = op.operands.split_first() else {
+            self.emit_const(math_identity(&op.op_type), op.span);
+            return Ok(());
+        };
+
+        self.compile(first)?;
+        for operand in rest {
+            self.compile(operand)?;
+            self.chunk
+                .emit(Instruction::Math(op.op_type.clone()), op.span);
+        }
+        Ok(())
+    }
+
+    fn compile_logical(&mut self, op: &LogicalOp) -> Result<(), SpannedError> {
+        if let LogicalOpType::Not = op.op_type {
+            self.compile(&op.operands[0])?;
+            self.chunk.emit(Instruction::Not, op.operands[0].get_span());
+            return Ok(());
+        }
+
+        // Operand value, that makes result of the whole operation known
+        let short = matches!(op.op_type, LogicalOpType::Or);
+
+        let mut shorts = Vec::new();
+        for operand in &op.operands {
+            self.compile(operand)?;
+            shorts.push(
+                self.chunk
+                    .emit(Instruction::JumpIf(short, 0), operand.get_span()),
+            );
+        }
+
+        self.emit_const(UVValue::Boolean(!short), op.span);
+        let exit = self.chunk.emit(Instruction::Jump(0), op.span);
+
+        for jump in shorts {
+            self.patch_jump(jump);
+        }
+        self.emit_const(UVValue::Boolean(short), op.span);
+
+        self.patch_jump(exit);
+        Ok(())
+    }
+
+    fn compile_for_loop(&mut self, for_loop: &ForLoop) -> Result<(), SpannedError> {
+        self.compile(&for_loop.start.value)?;
+        self.chunk
+            .emit(Instruction::CheckBound, for_loop.start.span);
+        self.compile(&for_loop.end.value)?;
+        self.chunk.emit(Instruction::CheckBound, for_loop.end.span);
+        match &for_loop.step {
+            Some(step) => {
+                self.compile(&step.value)?;
+                self.chunk.emit(Instruction::CheckBound, step.span);
+            }
+            None => self.emit_const(UVValue::Int(1), for_loop.span),
+        }
+
+        // Hidden slots for counter, end and step
+        let counter = self.chunk.locals;
+        self.chunk.locals += 3;
+        self.chunk
+            .emit(Instruction::ForInit(counter), for_loop.span);
+
+        let start = self
+            .chunk
+            .emit(Instruction::ForNext(counter, 0), for_loop.span);
+
+        // Each iteration gets its own scope with the iterator variable
+        self.scopes.push(HashMap::new());
+        let iterator = self.declare(&for_loop.iterator.value);
+        self.chunk
+            .emit(Instruction::Store(iterator), for_loop.iterator.span);
+        self.compile_block(&for_loop.body.value, for_loop.body.span)?;
+        self.chunk.emit(Instruction::Pop, for_loop.span);
+        self.scopes.pop();
+
+        self.chunk
+            .emit(Instruction::ForStep(counter), for_loop.span);
+        self.chunk.emit(Instruction::Jump(start), for_loop.span);
+
+        self.patch_jump(start);
+        self.emit_const(UVValue::Null, for_loop.span);
+        Ok(())
+    }
+
+    fn compile_call(&mut self, call: &FunctionCall) -> Result<(), SpannedError> {
+        let signature = self.signatures.get(&call.callee.value).ok_or(
+            SpannedError::new(
+                format!("Cannot find function `{}`", call.callee.value),
+                call.callee.span,
+            )
+            .with_code(ErrorCode::UnresolvedName),
+        )?;
+        let index = signature.index;
+        let params = signature.params.clone();
+
+        // Position of the parameter for each argument
+        let mut targets = Vec::new();
+        for (position, arg) in call.args.iter().enumerate() {
+            let target = match &arg.name {
+                Some(name) => params.iter().position(|p| *p == name.value),
+                None => (position < params.len()).then_some(position),
+            }
+            .ok_or(
+                SpannedError::new(
+                    format!("Unexpected argument for function `{}`", call.callee.value),
+                    arg.span,
+                )
+                .with_code(ErrorCode::InvalidArguments),
+            )?;
+            targets.push(target);
+        }
+
+        if let Some(missing) = params
+            .iter()
+            .enumerate()
+            .find(|(i, _)| !targets.contains(i))
+        {
+            return Err(SpannedError::new(
+                format!("Missing argument for parameter `{}`", missing.1),
+                call.span,
+            )
+            .with_code(ErrorCode::InvalidArguments));
+        }
+
+        if targets.iter().copied().eq(0..params.len()) {
+            for arg in &call.args {
+                self.compile(&arg.value.value)?;
+            }
+        } else {
+            // Arguments are evaluated in the call order, then passed in the parameters order
+            let mut temporaries = vec![0; params.len()];
+            for (arg, target) in call.args.iter().zip(targets) {
+                self.compile(&arg.value.value)?;
+                let slot = self.chunk.locals;
+                self.chunk.locals += 1;
+                self.chunk.emit(Instruction::Store(slot), arg.span);
+                temporaries[target] = slot;
+            }
+
+            for slot in temporaries {
+                self.chunk.emit(Instruction::Load(slot), call.span);
+            }
+        }
+
+        self.chunk.emit(Instruction::Call(index), call.span);
+        Ok(())
+    }
+
+    /// Allocate slot for a new variable in the current scope
+    fn declare(&mut self, name: &str) -> Slot {
+        let slot = self.chunk.locals;
+        self.chunk.locals += 1;
+        self.scopes
+            .last_mut()
+            .expect("Chunk always has a scope")
+            .insert(name.to_owned(), slot);
+        slot
+    }
+
+    fn lookup(&self, name: &str, span: Span) -> Result<Slot, SpannedError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .ok_or(
+                SpannedError::new(format!("Cannot find variable `{name}` in this scope"), span)
+                    .with_code(ErrorCode::UnresolvedName),
+            )
+    }
+
+    fn emit_const(&mut self, value: UVValue, span: Span) {
+        let index = match self.constants.iter().position(|c| same_constant(c, &value)) {
+            Some(index) => index,
+            None => {
+                self.constants.push(value);
+                self.constants.len() - 1
+            }
+        };
+        self.chunk.emit(Instruction::Const(index), span);
+    }
+
+    /// Point jump instruction to the next emitted instruction
+    fn patch_jump(&mut self, at: usize) {
+        let target = self.chunk.code.len();
+        match &mut self.chunk.code[at] {
+            Instruction::Jump(to) | Instruction::JumpIf(_, to) | Instruction::ForNext(_, to) => {
+                *to = target
+            }
+            _ => unreachable!("Only jump instructions can be patched"),
+        }
+    }
+}
+
+/// Check if constants are interchangeable (`0.0` and `-0.0` are not)
+fn same_constant(a: &UVValue, b: &UVValue) -> bool {
+    match (a, b) {
+        (UVValue::Float(a), UVValue::Float(b)) => a.to_bits() == b.to_bits(),
+        _ => a == b,
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::{traits::GetType, types::UVValue},
+    types::SourceFile,
+    vm::instructions::{Instruction, Program},
+};
+
+/// Render program as human-readable listing.
+/// Each instruction is annotated with its location in the `source`
+pub fn disassemble(program: &Program, source: &SourceFile) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "constants:").unwrap();
+    for (index, constant) in program.constants.iter().enumerate() {
+        writeln!(output, "    #{index} = {}", render_constant(constant)).unwrap();
+    }
+
+    for (index, chunk) in program.functions.iter().enumerate() {
+        let entry = if index == program.entry {
+            " (entry)"
+        } else {
+            ""
+        };
+        writeln!(
+            output,
+            "\nfn {}{entry}: {} params, {} locals",
+            chunk.name, chunk.arity, chunk.locals
+        )
+        .unwrap();
+
+        for (pc, (instruction, span)) in chunk.code.iter().zip(&chunk.spans).enumerate() {
+            let (line, col) = source.get_line_col(*span);
+            let location = format!("{}:{}:{}", source.path.to_string_lossy(), line + 1, col);
+
+            writeln!(
+                output,
+                "    {pc:04}  {:<24} ; {location}",
+                render_instruction(instruction, program)
+            )
+            .unwrap();
+        }
+    }
+
+    output
+}
+
+fn render_constant(value: &UVValue) -> String {
+    match value {
+        UVValue::String(s) => format!("str {s:?}"),
+        UVValue::Null => "null".to_owned(),
+        other => format!("{} {other}", other.get_type()),
+    }
+}
+
+fn render_instruction(instruction: &Instruction, program: &Program) -> String {
+    match instruction {
+        Instruction::Const(index) => match program.constants.get(*index) {
+            Some(value) => format!("CONST #{index} ({})", render_constant(value)),
+            None => format!("CONST #{index}"),
+        },
+        Instruction::Pop => "POP".to_owned(),
+        Instruction::Load(slot) => format!("LOAD {slot}"),
+        Instruction::Store(slot) => format!("STORE {slot}"),
+        Instruction::Math(op) => format!("MATH <{op}>"),
+        Instruction::Compare(op) => format!("COMPARE <{op}>"),
+        Instruction::Not => "NOT".to_owned(),
+        Instruction::CheckBound => "CHECK_BOUND".to_owned(),
+        Instruction::Jump(target) => format!("JUMP {target:04}"),
+        Instruction::JumpIf(expected, target) => format!("JUMP_IF {expected} {target:04}"),
+        Instruction::ForInit(slot) => format!("FOR_INIT {slot}"),
+        Instruction::ForNext(slot, exit) => format!("FOR_NEXT {slot} {exit:04}"),
+        Instruction::ForStep(slot) => format!("FOR_STEP {slot}"),
+        Instruction::Call(index) => match program.functions.get(*index) {
+            Some(chunk) => format!("CALL {index} ({})", chunk.name),
+            None => format!("CALL {index}"),
+        },
+        Instruction::Return => "RETURN".to_owned(),
+        Instruction::Print(count) => format!("PRINT {count}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        interpreter::tests::parse,
+        types::SourceFile,
+        vm::{compile, disassembler::disassemble},
+    };
+
+    #[test]
+    fn listing() {
+        let main = "\n<println><sum><str>a</str><int>1</int></sum></println>\n";
+        let code = format!("<program><main>{main}</main></program>");
+        let source = SourceFile::new(Path::new("test.uv"), code);
+
+        let program = compile(&parse(main)).unwrap();
+        assert_eq!(
+            disassemble(&program, &source),
+            "constants:\n\
+            \x20   #0 = str \"a\"\n\
+            \x20   #1 = int 1\n\
+            \x20   #2 = null\n\
+            \n\
+            fn <main> (entry): 0 params, 0 locals\n\
+            \x20   0000  CONST #0 (str \"a\")       ; test.uv:2:14\n\
+            \x20   0001  CONST #1 (int 1)         ; test.uv:2:26\n\
+            \x20   0002  MATH <sum>               ; test.uv:2:9\n\
+            \x20   0003  PRINT 1                  ; test.uv:2:0\n\
+            \x20   0004  CONST #2 (null)          ; test.uv:2:0\n\
+            \x20   0005  RETURN                   ; test.uv:1:0\n"
+        );
+    }
+}
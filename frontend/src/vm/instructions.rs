@@ -0,0 +1,95 @@
+use crate::{
+    ast::types::{CompareOpType, MathOpType, UVValue},
+    types::Span,
+};
+
+/// Index of the local variable slot inside the call frame
+pub type Slot = usize;
+
+/// Single instruction of the stack machine
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    /// Push value from the constant pool
+    Const(usize),
+    /// Drop value from the top of the stack
+    Pop,
+
+    /// Push value of the local variable
+    Load(Slot),
+    /// Pop value into the local variable
+    Store(Slot),
+
+    /// Pop right and left operands, push result
+    Math(MathOpType),
+    /// Pop right and left operands, push boolean result
+    Compare(CompareOpType),
+    /// Pop boolean, push negated value
+    Not,
+
+    /// Fail if for loop bound on the top of the stack is not a number
+    CheckBound,
+
+    /// Unconditional jump to the instruction
+    Jump(usize),
+    /// Pop boolean and jump if it is equal to the expected value
+    JumpIf(bool, usize),
+
+    /// Pop step, end and start of the for loop into three consecutive slots
+    ForInit(Slot),
+    /// Push next value of the for loop counter or jump out of the loop
+    ForNext(Slot, usize),
+    /// Advance for loop counter by its step
+    ForStep(Slot),
+
+    /// Call function with arguments on the top of the stack
+    Call(usize),
+    /// Pop value and return it to the caller
+    Return,
+
+    /// Pop values and print them on a single line
+    Print(usize),
+}
+
+/// Compiled function body
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub name: String,
+    pub arity: usize,
+
+    /// Number of local variable slots, including parameters
+    pub locals: usize,
+
+    pub code: Vec<Instruction>,
+
+    /// Source location of each instruction
+    pub spans: Vec<Span>,
+}
+
+impl Chunk {
+    pub fn new(name: impl Into<String>, arity: usize) -> Self {
+        Self {
+            name: name.into(),
+            arity,
+            locals: arity,
+            code: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Append instruction and get its index
+    pub fn emit(&mut self, instruction: Instruction, span: Span) -> usize {
+        self.code.push(instruction);
+        self.spans.push(span);
+        self.code.len() - 1
+    }
+}
+
+/// Compiled program
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub constants: Vec<UVValue>,
+    pub functions: Vec<Chunk>,
+
+    /// Index of the chunk with top-level code
+    pub entry: usize,
+}
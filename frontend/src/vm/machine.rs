@@ -0,0 +1,233 @@
+use std::io::Write;
+
+use crate::{
+    ast::{
+        traits::GetType,
+        types::{CompareOpType, MathOpType, UVValue},
+    },
+    errors::{SpannedError, codes::ErrorCode},
+    interpreter::{
+        MAX_CALL_DEPTH,
+        operations::{as_bool, binary_math, compare},
+    },
+    types::Span,
+    vm::instructions::{Instruction, Program},
+};
+
+/// State of a single function invocation
+struct CallFrame {
+    /// Index of the executed chunk
+    chunk: usize,
+    pc: usize,
+
+    /// Stack position of the first local slot
+    base: usize,
+
+    /// Stack position above the local slots. Instructions of the frame
+    /// cannot pop values below it
+    floor: usize,
+}
+
+/// Stack-based virtual machine.
+///
+/// Output of <println> is written to the provided writer
+pub struct Machine<W: Write> {
+    stack: Vec<UVValue>,
+    frames: Vec<CallFrame>,
+    output: W,
+}
+
+impl<W: Write> Machine<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            stack: Vec::new(),
+            frames: Vec::new(),
+            output,
+        }
+    }
+
+    /// Get writer back
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Execute program and get value of the last top-level statement
+    pub fn run(&mut self, program: &Program) -> Result<UVValue, SpannedError> {
+        self.stack.clear();
+        self.frames.clear();
+        self.enter(program, program.entry, Span::new(0, 0))?;
+
+        loop {
+            let frame = self.frames.last_mut().expect("Machine has an active frame");
+            let chunk = &program.functions[frame.chunk];
+            let (pc, base) = (frame.pc, frame.base);
+            frame.pc += 1;
+
+            let span = chunk.spans[pc];
+            match &chunk.code[pc] {
+                Instruction::Const(index) => self.stack.push(program.constants[*index].clone()),
+                Instruction::Pop => {
+                    self.pop(span)?;
+                }
+                Instruction::Load(slot) => self.stack.push(self.stack[base + slot].clone()),
+                Instruction::Store(slot) => self.stack[base + slot] = self.pop(span)?,
+                Instruction::Math(op) => {
+                    let right = self.pop(span)?;
+                    let left = self.pop(span)?;
+                    self.stack.push(binary_math(op, &left, &right, span)?);
+                }
+                Instruction::Compare(op) => {
+                    let right = self.pop(span)?;
+                    let left = self.pop(span)?;
+                    self.stack
+                        .push(UVValue::Boolean(compare(op, &left, &right, span)?));
+                }
+                Instruction::Not => {
+                    let value = self.pop(span)?;
+                    self.stack.push(UVValue::Boolean(!as_bool(&value, span)?));
+                }
+                Instruction::CheckBound => {
+                    if let Some(value) = self.stack.last()
+                        && !matches!(value, UVValue::Int(_) | UVValue::Float(_))
+                    {
+                        return Err(SpannedError::new(
+                            format!(
+                                "For loop bounds must be numeric, found `{}`",
+                                value.get_type()
+                            ),
+                            span,
+                        )
+                        .with_code(ErrorCode::InvalidLoopBounds));
+                    }
+                }
+                Instruction::Jump(target) => self.jump(*target),
+                Instruction::JumpIf(expected, target) => {
+                    let value = self.pop(span)?;
+                    if as_bool(&value, span)? == *expected {
+                        self.jump(*target);
+                    }
+                }
+                Instruction::ForInit(slot) => {
+                    let step = self.pop(span)?;
+                    let end = self.pop(span)?;
+                    let start = self.pop(span)?;
+
+                    if compare(&CompareOpType::Equality, &step, &UVValue::Int(0), span)? {
+                        return Err(SpannedError::new("For loop step cannot be zero", span)
+                            .with_code(ErrorCode::InvalidLoopBounds));
+                    }
+
+                    let at = base + slot;
+                    self.stack[at..at + 3].clone_from_slice(&[start, end, step]);
+                }
+                Instruction::ForNext(slot, exit) => {
+                    let [current, end, step] = &self.stack[base + slot..base + slot + 3] else {
+                        unreachable!("For loop uses three slots");
+                    };
+
+                    let condition =
+                        if compare(&CompareOpType::Greater, step, &UVValue::Int(0), span)? {
+                            CompareOpType::Less
+                        } else {
+                            CompareOpType::Greater
+                        };
+
+                    if compare(&condition, current, end, span)? {
+                        self.stack.push(current.clone());
+                    } else {
+                        self.jump(*exit);
+                    }
+                }
+                Instruction::ForStep(slot) => {
+                    let at = base + slot;
+                    self.stack[at] =
+                        binary_math(&MathOpType::Sum, &self.stack[at], &self.stack[at + 2], span)?;
+                }
+                Instruction::Call(index) => {
+                    // Entry frame is not a function call
+                    if self.frames.len() > MAX_CALL_DEPTH {
+                        return Err(SpannedError::new(
+                            format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded"),
+                            span,
+                        )
+                        .with_code(ErrorCode::CallDepthExceeded));
+                    }
+                    self.enter(program, *index, span)?;
+                }
+                Instruction::Return => {
+                    let value = self.pop(span)?;
+                    let frame = self.frames.pop().expect("Machine has an active frame");
+                    self.stack.truncate(frame.base);
+
+                    if self.frames.is_empty() {
+                        return Ok(value);
+                    }
+                    self.stack.push(value);
+                }
+                Instruction::Print(count) => {
+                    let values = self.pop_many(*count, span)?;
+                    let line = values
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ");
+
+                    writeln!(self.output, "{line}").map_err(|err| {
+                        SpannedError::new(format!("Cannot write output: {err}"), span)
+                    })?;
+                }
+            }
+        }
+    }
+
+    /// Push frame for the chunk. Arguments on the top of the stack become its first slots
+    fn enter(&mut self, program: &Program, index: usize, span: Span) -> Result<(), SpannedError> {
+        let chunk = &program.functions[index];
+        if self.operands() < chunk.arity {
+            return Err(stack_underflow(span));
+        }
+
+        let base = self.stack.len() - chunk.arity;
+        self.stack.resize(base + chunk.locals, UVValue::Null);
+
+        self.frames.push(CallFrame {
+            chunk: index,
+            pc: 0,
+            base,
+            floor: base + chunk.locals,
+        });
+        Ok(())
+    }
+
+    /// Count of values, that the current frame can pop
+    fn operands(&self) -> usize {
+        let floor = self.frames.last().map_or(0, |frame| frame.floor);
+        self.stack.len() - floor
+    }
+
+    fn jump(&mut self, target: usize) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.pc = target;
+        }
+    }
+
+    fn pop(&mut self, span: Span) -> Result<UVValue, SpannedError> {
+        if self.operands() == 0 {
+            return Err(stack_underflow(span));
+        }
+        Ok(self.stack.pop().expect("Frame has operands"))
+    }
+
+    fn pop_many(&mut self, count: usize, span: Span) -> Result<Vec<UVValue>, SpannedError> {
+        if self.operands() < count {
+            return Err(stack_underflow(span));
+        }
+        Ok(self.stack.split_off(self.stack.len() - count))
+    }
+}
+
+/// Malformed bytecode can pop more values than it pushed,
+/// so the stack depth is checked on every pop
+fn stack_underflow(span: Span) -> SpannedError {
+    SpannedError::new("[INTERNAL ERROR] Stack underflow", span)
+}
@@ -0,0 +1,60 @@
+use crate::{
+    ast::types::ASTBlockType,
+    errors::SpannedError,
+    vm::{compiler::Compiler, instructions::Program},
+};
+
+pub mod bytecode;
+pub mod compiler;
+pub mod disassembler;
+pub mod instructions;
+pub mod machine;
+
+/// Compile checked AST into the program for [`machine::Machine`]
+pub fn compile(program: &ASTBlockType) -> Result<Program, SpannedError> {
+    Compiler::new().compile_program(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::tests::{CASES, ERROR_CASES, parse},
+        types::{Positional, Span},
+        vm::{compile, machine::Machine},
+    };
+
+    fn run(main: &str) -> Result<String, String> {
+        let program = compile(&parse(main)).map_err(|err| err.message().to_owned())?;
+
+        let mut machine = Machine::new(Vec::new());
+        machine
+            .run(&program)
+            .map_err(|err| err.message().to_owned())?;
+
+        Ok(String::from_utf8(machine.into_output()).unwrap())
+    }
+
+    #[test]
+    fn programs() {
+        for (program, expected) in CASES {
+            assert_eq!(run(program).as_deref(), Ok(*expected), "{program}");
+        }
+    }
+
+    #[test]
+    fn runtime_errors() {
+        for (program, expected) in ERROR_CASES {
+            assert_eq!(run(program), Err(expected.to_string()), "{program}");
+        }
+    }
+
+    #[test]
+    fn runtime_error_span() {
+        let main = "<println><div><int>1</int><int>0</int></div></println>";
+        let program = compile(&parse(main)).unwrap();
+        let err = Machine::new(Vec::new()).run(&program).unwrap_err();
+
+        // Offset of <div> inside `<program><main>`
+        assert_eq!(err.get_span(), Span::new(24, 59));
+    }
+}
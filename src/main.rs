@@ -1,9 +1,352 @@
-use anyhow::{Ok, Result};
-use frontend;
-use std::path::Path;
+use std::{
+    fmt,
+    io::{BufRead, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
 
-fn main() -> Result<()> {
-    let _ = frontend::process(Path::new("./examples/file.uv"));
+use anyhow::Result;
+use clap::{Parser, Subcommand, ValueEnum};
+use frontend::{
+    Backend, Diagnostics, ErrorCode, ErrorRenderer, Machine, Output, Program, Repl, ReplOutput,
+    SourceFile, SpannedError, UVValue, diagnostic_to_json, diagnostic_to_sarif, disassemble,
+    sarif_log,
+};
+use serde_json::Value;
+
+/// Ultraviolet language toolchain
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Command to execute. Starts REPL when omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Print detailed explanation of the error code, e.g. UV0001
+    #[arg(long, value_name = "CODE", exclusive = true)]
+    explain: Option<String>,
+
+    /// Format of the reported diagnostics
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print lexer tokens
+    Tokens { file: PathBuf },
+
+    /// Print parse tree
+    Parse { file: PathBuf },
+
+    /// Print AST without semantic checks
+    Ast { file: PathBuf },
+
+    /// Resolve names and check types
+    Check {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Check and execute program
+    Run {
+        file: PathBuf,
+
+        /// Execution backend
+        #[arg(long, value_enum, default_value_t = BackendArg::Vm)]
+        backend: BackendArg,
+    },
+
+    /// Compile program to bytecode file
+    Build {
+        file: PathBuf,
+
+        /// Output bytecode file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Execute bytecode file
+    Exec {
+        bytecode: PathBuf,
+
+        /// Source file of the bytecode, used to render runtime errors
+        #[arg(long)]
+        source: Option<PathBuf>,
+    },
+
+    /// Print bytecode listing
+    Disasm {
+        bytecode: PathBuf,
+
+        /// Source file of the bytecode
+        #[arg(long)]
+        source: PathBuf,
+    },
+
+    /// Start interactive session
+    Repl,
+}
+
+#[derive(Clone, ValueEnum)]
+enum BackendArg {
+    Interpreter,
+    Vm,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MessageFormat {
+    /// Rendered text with source snippets on stderr
+    Human,
+
+    /// JSON record per diagnostic on stderr
+    Json,
+
+    /// SARIF 2.1.0 log on stderr, printed when the command finishes
+    Sarif,
+}
+
+impl MessageFormat {
+    fn is_human(self) -> bool {
+        matches!(self, MessageFormat::Human)
+    }
+}
+
+/// Error returned when diagnostics were already printed to stderr
+#[derive(Debug)]
+struct DiagnosticsReported(usize);
+
+impl fmt::Display for DiagnosticsReported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            1 => write!(f, "aborting due to previous error"),
+            count => write!(f, "aborting due to {count} previous errors"),
+        }
+    }
+}
+
+impl std::error::Error for DiagnosticsReported {}
+
+/// Prints diagnostics in the selected format.
+///
+/// All formats are written to stderr, so stdout is left to the command output (e.g. `run`)
+struct Reporter {
+    format: MessageFormat,
+
+    /// SARIF results are printed as a single log by `finish`
+    sarif_results: Vec<Value>,
+}
+
+impl Reporter {
+    fn new(format: MessageFormat) -> Self {
+        Self {
+            format,
+            sarif_results: Vec::new(),
+        }
+    }
+
+    /// Print diagnostics and get error to abort the command
+    fn report(&mut self, source: &SourceFile, diagnostics: Diagnostics) -> anyhow::Error {
+        self.print(source, &diagnostics);
+        DiagnosticsReported(diagnostics.iter().filter(|d| d.is_error()).count()).into()
+    }
+
+    /// Print warnings of the passed stage and get its value, or abort on errors
+    fn finish_stage<T>(
+        &mut self,
+        source: &SourceFile,
+        result: Result<Output<T>, Diagnostics>,
+    ) -> Result<T> {
+        match result {
+            Ok(output) => {
+                self.print(source, &output.warnings);
+                Ok(output.value)
+            }
+            Err(diagnostics) => Err(self.report(source, diagnostics)),
+        }
+    }
+
+    fn print(&mut self, source: &SourceFile, diagnostics: &[SpannedError]) {
+        match self.format {
+            MessageFormat::Human => {
+                for diagnostic in diagnostics {
+                    eprintln!("{}", diagnostic.display_with_source(source));
+                }
+
+                if let Some(diagnostic) = diagnostics.iter().find(|d| d.code().is_some()) {
+                    let kind = match diagnostic.is_error() {
+                        true => "an error",
+                        false => "a warning",
+                    };
+                    eprintln!(
+                        "For more information about {kind}, try `{} --explain {}`.",
+                        env!("CARGO_BIN_NAME"),
+                        diagnostic.code().unwrap()
+                    );
+                }
+            }
+            MessageFormat::Json => {
+                for diagnostic in diagnostics {
+                    eprintln!("{}", diagnostic_to_json(diagnostic, source));
+                }
+            }
+            MessageFormat::Sarif => self.sarif_results.extend(
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic_to_sarif(diagnostic, source)),
+            ),
+        }
+    }
+
+    /// Print collected SARIF log
+    fn finish(self) -> Result<()> {
+        if let MessageFormat::Sarif = self.format {
+            eprintln!(
+                "{}",
+                serde_json::to_string_pretty(&sarif_log(self.sarif_results))?
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Print entry of the explain catalog
+fn explain(code: &str) -> Result<()> {
+    let code = ErrorCode::lookup(code)
+        .ok_or_else(|| anyhow::anyhow!("`{code}` is not a valid error code"))?;
+    let explanation = code.explain();
+
+    println!("{code}: {}\n", explanation.title);
+    println!("{}\n", explanation.description);
+    println!("Erroneous code example:\n\n{}\n", explanation.erroneous);
+    println!("Fixed example:\n\n{}", explanation.fixed);
+    Ok(())
+}
+
+fn execute(command: Command, reporter: &mut Reporter) -> Result<()> {
+    match command {
+        Command::Tokens { file } => {
+            let source = SourceFile::load(&file)?;
+            for token in frontend::tokenize(&source) {
+                let (line, col) = source.get_line_col(token.span);
+                println!("{}:{}\t{}", line + 1, col, token.token);
+            }
+        }
+        Command::Parse { file } => {
+            let source = SourceFile::load(&file)?;
+            let tree =
+                frontend::parse(&source).map_err(|errors| reporter.report(&source, errors))?;
+            println!("{tree:#?}");
+        }
+        Command::Ast { file } => {
+            let source = SourceFile::load(&file)?;
+            let ast = reporter.finish_stage(&source, frontend::parse_ast(&source))?;
+            println!("{ast:#?}");
+        }
+        Command::Check { files } => check(&files, reporter)?,
+        Command::Run { file, backend } => {
+            let backend = match backend {
+                BackendArg::Interpreter => Backend::Interpreter,
+                BackendArg::Vm => Backend::Vm,
+            };
+
+            let source = SourceFile::load(&file)?;
+            let result = frontend::run(&source, backend, std::io::stdout().lock());
+            reporter.finish_stage(&source, result)?;
+        }
+        Command::Build { file, output } => {
+            let source = SourceFile::load(&file)?;
+            let program = reporter.finish_stage(&source, frontend::compile(&source))?;
+            program.save(&output.unwrap_or_else(|| file.with_extension("uvc")))?;
+        }
+        Command::Exec { bytecode, source } => {
+            let program = Program::load(&bytecode)?;
+            if let Err(err) = Machine::new(std::io::stdout().lock()).run(&program) {
+                return Err(match source {
+                    Some(path) => reporter.report(&SourceFile::load(&path)?, vec![err]),
+                    None => anyhow::anyhow!("{}", err.message()),
+                });
+            }
+        }
+        Command::Disasm { bytecode, source } => {
+            let program = Program::load(&bytecode)?;
+            print!("{}", disassemble(&program, &SourceFile::load(&source)?));
+        }
+        Command::Repl => repl()?,
+    }
 
     Ok(())
 }
+
+/// Check all files, even if some of them have errors
+fn check(files: &[PathBuf], reporter: &mut Reporter) -> Result<()> {
+    let mut errors = 0;
+    for file in files {
+        let result = SourceFile::load(file)
+            .map(|source| reporter.finish_stage(&source, frontend::check(&source)));
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => errors += err.downcast_ref::<DiagnosticsReported>().map_or(1, |r| r.0),
+            Err(err) => {
+                eprintln!("error: {err:#}");
+                errors += 1;
+            }
+        }
+    }
+
+    match errors {
+        0 => Ok(()),
+        count => Err(DiagnosticsReported(count).into()),
+    }
+}
+
+/// Interactive session over stdin
+fn repl() -> Result<()> {
+    let mut repl = Repl::new(std::io::stdout());
+    let mut lines = std::io::stdin().lock().lines();
+
+    loop {
+        print!("{}", if repl.is_pending() { "... " } else { ">>> " });
+        std::io::stdout().flush()?;
+
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        let line = line?;
+
+        if !repl.is_pending() && line.trim().is_empty() {
+            continue;
+        }
+
+        match repl.feed_line(&line) {
+            ReplOutput::Pending | ReplOutput::Value(UVValue::Null) => {}
+            ReplOutput::Value(value) => println!("{value}"),
+            ReplOutput::Error(err) => eprintln!("{err}"),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut reporter = Reporter::new(cli.message_format);
+    let result = match cli.explain {
+        Some(code) => explain(&code),
+        None => execute(cli.command.unwrap_or(Command::Repl), &mut reporter),
+    };
+    let result = result.and(reporter.finish());
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Summary would break machine-readable diagnostics, exit code is enough for them
+        Err(err) if err.is::<DiagnosticsReported>() && !cli.message_format.is_human() => {
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::{fs, path::Path};
+
+use assert_cmd::{Command, assert::Assert};
+use serde_json::Value;
+use tempfile::TempDir;
+
+const OK: &str =
+    "<program><main><println><sum><int>1</int><int>2</int></sum></println></main></program>";
+const UNRESOLVED: &str = "<program><main>\n<println><x /></println>\n</main></program>";
+const DIVISION_BY_ZERO: &str =
+    "<program><main><div><int>1</int><int>0</int></div></main></program>";
+const TRUNCATED: &str = "<program><main><a>";
+const XML_DECLARATION: &str = "<?xml version=\"1.0\"?>\n\
+    <program><main>\n\
+    <println><int>2</int></println>\n\
+    </main></program>";
+
+/// Temporary directory with source files. Commands are executed inside of it,
+/// so diagnostics contain short relative paths
+struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    fn new(files: &[(&str, &str)]) -> Self {
+        let dir = TempDir::new().unwrap();
+        for (name, code) in files {
+            fs::write(dir.path().join(name), code).unwrap();
+        }
+        Self { dir }
+    }
+
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    fn cmd(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_Ultraviolet"));
+        cmd.current_dir(self.path()).args(args);
+        cmd
+    }
+}
+
+fn stdout(assert: &Assert) -> String {
+    String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+}
+
+fn stderr(assert: &Assert) -> String {
+    String::from_utf8(assert.get_output().stderr.clone()).unwrap()
+}
+
+#[test]
+fn tokens() {
+    let fixture = Fixture::new(&[("ok.uv", OK)]);
+
+    let assert = fixture.cmd(&["tokens", "ok.uv"]).assert().success();
+    assert!(stdout(&assert).starts_with("1:0\t<\n1:1\t[Literal \"program\"]\n1:8\t>\n"));
+}
+
+#[test]
+fn parse_and_ast() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("truncated.uv", TRUNCATED)]);
+
+    let assert = fixture.cmd(&["parse", "ok.uv"]).assert().success();
+    assert!(stdout(&assert).contains("name: \"program\""));
+
+    let assert = fixture.cmd(&["ast", "ok.uv"]).assert().success();
+    assert!(stdout(&assert).starts_with("Program("));
+
+    let assert = fixture
+        .cmd(&["parse", "truncated.uv"])
+        .assert()
+        .code(1)
+        .stdout("");
+    let stderr = stderr(&assert);
+    assert!(stderr.starts_with("error[UV0003]: Unexpected EOF\n --> truncated.uv:1:17\n"));
+    assert!(stderr.ends_with("error: aborting due to previous error\n"));
+}
+
+#[test]
+fn check() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("bad.uv", UNRESOLVED)]);
+
+    fixture
+        .cmd(&["check", "ok.uv"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let assert = fixture
+        .cmd(&["check", "bad.uv"])
+        .assert()
+        .code(1)
+        .stdout("");
+    assert_eq!(
+        stderr(&assert),
+        "error[UV0010]: Cannot find variable `x` in this scope\n \
+        --> bad.uv:2:9\n   \
+        |\n \
+        2 | <println><x /></println>\n   \
+        |          ^^^^^\n\n\
+        For more information about an error, try `Ultraviolet --explain UV0010`.\n\
+        error: aborting due to previous error\n"
+    );
+}
+
+#[test]
+fn check_reports_all_files() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("bad.uv", UNRESOLVED)]);
+
+    let assert = fixture
+        .cmd(&["check", "bad.uv", "missing.uv", "ok.uv"])
+        .assert()
+        .code(1);
+    let stderr = stderr(&assert);
+    assert!(stderr.contains("error[UV0010]"));
+    assert!(stderr.contains("error: Cannot read missing.uv"));
+    assert!(stderr.ends_with("error: aborting due to 2 previous errors\n"));
+}
+
+#[test]
+fn warnings_do_not_fail() {
+    let fixture = Fixture::new(&[("xml.uv", XML_DECLARATION)]);
+    let warning = "warning[UV0023]: Unknown processing instruction `xml`\n \
+        --> xml.uv:1:2\n";
+
+    let assert = fixture.cmd(&["check", "xml.uv"]).assert().success();
+    let output = stderr(&assert);
+    assert!(output.starts_with(warning));
+    assert!(output.contains("= note: the instruction is ignored"));
+    assert!(
+        output.ends_with(
+            "For more information about a warning, try `Ultraviolet --explain UV0023`.\n"
+        )
+    );
+    assert!(!output.contains("aborting due to"));
+
+    for backend in ["vm", "interpreter"] {
+        let assert = fixture
+            .cmd(&["run", "xml.uv", "--backend", backend])
+            .assert()
+            .success()
+            .stdout("2\n");
+        let stderr = stderr(&assert);
+        assert!(stderr.starts_with(warning));
+        assert!(!stderr.contains("aborting due to"));
+    }
+
+    fixture.cmd(&["build", "xml.uv"]).assert().success();
+
+    let assert = fixture
+        .cmd(&["check", "xml.uv", "--message-format", "json"])
+        .assert()
+        .success()
+        .stdout("");
+    let diagnostic: Value = serde_json::from_str(stderr(&assert).trim()).unwrap();
+    assert_eq!(diagnostic["severity"], "warning");
+    assert_eq!(diagnostic["code"], "UV0023");
+}
+
+#[test]
+fn run() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("zero.uv", DIVISION_BY_ZERO)]);
+
+    for backend in ["vm", "interpreter"] {
+        fixture
+            .cmd(&["run", "ok.uv", "--backend", backend])
+            .assert()
+            .success()
+            .stdout("3\n")
+            .stderr("");
+
+        let assert = fixture
+            .cmd(&["run", "zero.uv", "--backend", backend])
+            .assert()
+            .code(1);
+        let stderr = stderr(&assert);
+        assert!(stderr.starts_with("error[UV0017]: Division by zero\n --> zero.uv:1:15\n"));
+        assert!(stderr.ends_with("error: aborting due to previous error\n"));
+    }
+
+    let assert = fixture.cmd(&["run", "missing.uv"]).assert().code(1);
+    assert!(stderr(&assert).starts_with("error: Cannot read missing.uv"));
+}
+
+#[test]
+fn build_and_exec() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("zero.uv", DIVISION_BY_ZERO)]);
+
+    fixture
+        .cmd(&["build", "ok.uv"])
+        .assert()
+        .success()
+        .stdout("");
+    assert!(fixture.path().join("ok.uvc").exists());
+    fixture
+        .cmd(&["exec", "ok.uvc"])
+        .assert()
+        .success()
+        .stdout("3\n");
+
+    fixture
+        .cmd(&["build", "zero.uv", "-o", "out.bin"])
+        .assert()
+        .success();
+    fixture
+        .cmd(&["exec", "out.bin"])
+        .assert()
+        .code(1)
+        .stderr("error: Division by zero\n");
+
+    let assert = fixture
+        .cmd(&["exec", "out.bin", "--source", "zero.uv"])
+        .assert()
+        .code(1);
+    let stderr = stderr(&assert);
+    assert!(stderr.starts_with("error[UV0017]: Division by zero\n --> zero.uv:1:15\n"));
+    assert!(stderr.ends_with("error: aborting due to previous error\n"));
+
+    fs::write(fixture.path().join("junk.uvc"), "junk").unwrap();
+    fixture
+        .cmd(&["exec", "junk.uvc"])
+        .assert()
+        .code(1)
+        .stderr("error: Invalid bytecode: missing `UVBC` header\n");
+}
+
+#[test]
+fn disasm() {
+    let fixture = Fixture::new(&[("ok.uv", OK)]);
+
+    fixture.cmd(&["build", "ok.uv"]).assert().success();
+    let assert = fixture
+        .cmd(&["disasm", "ok.uvc", "--source", "ok.uv"])
+        .assert()
+        .success();
+    let stdout = stdout(&assert);
+    assert!(stdout.starts_with("constants:\n    #0 = int 1\n"));
+    assert!(stdout.contains("fn <main> (entry): 0 params, 0 locals\n"));
+    assert!(stdout.contains("    0000  CONST #0 (int 1)         ; ok.uv:1:29\n"));
+}
+
+#[test]
+fn repl() {
+    let fixture = Fixture::new(&[]);
+
+    let assert = fixture
+        .cmd(&["repl"])
+        .write_stdin(
+            "<let><name>x</name>\n<value><int>2</int></value></let>\n<mul><x /><int>21</int></mul>\n<y />\n",
+        )
+        .assert()
+        .success();
+    assert_eq!(stdout(&assert), ">>> ... >>> 42\n>>> >>> \n");
+    assert!(stderr(&assert).contains("error[UV0010]: Cannot find variable `y` in this scope"));
+
+    // REPL is the default command
+    fixture
+        .cmd(&[])
+        .write_stdin("<int>7</int>\n")
+        .assert()
+        .success()
+        .stdout(">>> 7\n>>> \n");
+}
+
+#[test]
+fn explain() {
+    let fixture = Fixture::new(&[]);
+
+    let assert = fixture
+        .cmd(&["--explain", "uv0012"])
+        .assert()
+        .success()
+        .stderr("");
+    let stdout = stdout(&assert);
+    assert!(stdout.starts_with("UV0012: Assignment to constant\n\n"));
+    assert!(stdout.contains("Erroneous code example:\n\n"));
+    assert!(stdout.contains("Fixed example:\n\n"));
+
+    fixture
+        .cmd(&["--explain", "UV9999"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("error: `UV9999` is not a valid error code\n");
+
+    // `--explain` cannot be combined with other options
+    fixture
+        .cmd(&["--explain", "UV0012", "--message-format", "json"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn json_message_format() {
+    let fixture = Fixture::new(&[("bad.uv", UNRESOLVED)]);
+
+    let assert = fixture
+        .cmd(&["check", "bad.uv", "--message-format", "json"])
+        .assert()
+        .code(1)
+        .stdout("");
+
+    let stderr = stderr(&assert);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 1);
+
+    let diagnostic: Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(diagnostic["code"], "UV0010");
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["file"], "bad.uv");
+    assert_eq!(diagnostic["span"]["line"], 1);
+    assert_eq!(diagnostic["span"]["column"], 9);
+}
+
+#[test]
+fn sarif_message_format() {
+    let fixture = Fixture::new(&[("ok.uv", OK), ("bad file.uv", UNRESOLVED)]);
+
+    let assert = fixture
+        .cmd(&["--message-format", "sarif", "check", "bad file.uv", "ok.uv"])
+        .assert()
+        .code(1)
+        .stdout("");
+
+    let log: Value = serde_json::from_str(&stderr(&assert)).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "UV0010");
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "bad%20file.uv"
+    );
+
+    // Log is printed even if there is nothing to report
+    let assert = fixture
+        .cmd(&["--message-format", "sarif", "check", "ok.uv"])
+        .assert()
+        .success();
+    let log: Value = serde_json::from_str(&stderr(&assert)).unwrap();
+    assert!(log["runs"][0]["results"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn sarif_message_format_of_run() {
+    let fixture = Fixture::new(&[("xml.uv", XML_DECLARATION), ("zero.uv", DIVISION_BY_ZERO)]);
+
+    // Program output and diagnostics do not mix
+    let assert = fixture
+        .cmd(&["run", "xml.uv", "--message-format", "sarif"])
+        .assert()
+        .success()
+        .stdout("2\n");
+    let log: Value = serde_json::from_str(&stderr(&assert)).unwrap();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "UV0023");
+    assert_eq!(results[0]["level"], "warning");
+
+    let assert = fixture
+        .cmd(&["run", "zero.uv", "--message-format", "sarif"])
+        .assert()
+        .code(1)
+        .stdout("");
+    let log: Value = serde_json::from_str(&stderr(&assert)).unwrap();
+    assert_eq!(log["runs"][0]["results"][0]["ruleId"], "UV0017");
+}